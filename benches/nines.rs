@@ -6,10 +6,31 @@ extern crate test;
 use bignum::types::*;
 use bignum::basic_ops::*;
 use bignum::karatsuba::*;
+use bignum::toom_cook::*;
+use bignum::ntt::*;
+use bignum::mul::*;
+use bignum::parallel::*;
 use test::Bencher;
 
+fn try_add(bencher: &mut Bencher, num_nines: usize) {
+    let nines = "9".repeat(num_nines);
+    let a = from_string(&nines).unwrap();
+    let b = a.clone();
+    bencher.iter(|| bignum_add(&a, &b));
+}
+
+#[bench]
+fn add_thousand(bencher: &mut Bencher) {
+    try_add(bencher, 1000);
+}
+
+#[bench]
+fn add_five_thousand(bencher: &mut Bencher) {
+    try_add(bencher, 5000);
+}
+
 fn try_long_mult(bencher: &mut Bencher, num_nines: usize) {
-    let nines = std::iter::repeat("9").take(num_nines).collect::<String>();
+    let nines = "9".repeat(num_nines);
     let a = from_string(&nines).unwrap();
     let b = a.clone();
     bencher.iter(|| bignum_long_mult(&a, &b));
@@ -25,8 +46,8 @@ fn long_mult_five_thousand(bencher: &mut Bencher) {
     try_long_mult(bencher, 5000);
 }
 
-fn try_karatsuba_mult(bencher: &mut Bencher, cutoff: usize, num_nines: usize, parallel: bool) {
-    let nines = std::iter::repeat("9").take(num_nines).collect::<String>();
+fn try_karatsuba_mult<P: Into<Parallelism> + Copy>(bencher: &mut Bencher, cutoff: usize, num_nines: usize, parallel: P) {
+    let nines = "9".repeat(num_nines);
     let a = from_string(&nines).unwrap();
     let b = a.clone();
     bencher.iter(|| bignum_karatsuba_mult(&a, &b, cutoff, parallel));
@@ -92,3 +113,226 @@ fn karatsuba_par_five_thousand_cutoff_hundred(bencher: &mut Bencher) {
     try_karatsuba_mult(bencher, 100, 5000, true);
 }
 
+#[bench]
+fn karatsuba_par_fifty_thousand_top_level(bencher: &mut Bencher) {
+    try_karatsuba_mult(bencher, 50, 50000, true);
+}
+
+#[bench]
+fn karatsuba_par_fifty_thousand_depth_three(bencher: &mut Bencher) {
+    try_karatsuba_mult(bencher, 50, 50000, Parallelism { depth: 3, grain: 400 });
+}
+
+// Multiplies into buffers allocated once up front, where the owned version
+// allocates its output and its scratch space every time
+fn try_karatsuba_scratch(bencher: &mut Bencher, cutoff: usize, num_nines: usize) {
+    let nines = "9".repeat(num_nines);
+    let a = from_string(&nines).unwrap();
+    let b = a.clone();
+    let n = a.limbs().len();
+    let mut out = vec![0; 2 * n];
+    let mut scratch = vec![0; karatsuba_scratch_len(n)];
+
+    bencher.iter(|| {
+        karatsuba_mult_into(a.limbs(), b.limbs(), &mut out, &mut scratch, cutoff);
+        out[0]
+    });
+}
+
+#[bench]
+fn karatsuba_scratch_thousand_cutoff_fifty(bencher: &mut Bencher) {
+    try_karatsuba_scratch(bencher, 50, 1000);
+}
+
+#[bench]
+fn karatsuba_scratch_five_thousand_cutoff_twenty(bencher: &mut Bencher) {
+    try_karatsuba_scratch(bencher, 20, 5000);
+}
+
+#[bench]
+fn karatsuba_scratch_five_thousand_cutoff_fifty(bencher: &mut Bencher) {
+    try_karatsuba_scratch(bencher, 50, 5000);
+}
+
+fn try_sqr(bencher: &mut Bencher, num_nines: usize) {
+    let nines = "9".repeat(num_nines);
+    let a = from_string(&nines).unwrap();
+    bencher.iter(|| bignum_sqr(&a));
+}
+
+#[bench]
+fn sqr_thousand(bencher: &mut Bencher) {
+    try_sqr(bencher, 1000);
+}
+
+#[bench]
+fn sqr_five_thousand(bencher: &mut Bencher) {
+    try_sqr(bencher, 5000);
+}
+
+fn try_karatsuba_sqr(bencher: &mut Bencher, cutoff: usize, num_nines: usize) {
+    let nines = "9".repeat(num_nines);
+    let a = from_string(&nines).unwrap();
+    bencher.iter(|| bignum_karatsuba_sqr(&a, cutoff, false));
+}
+
+#[bench]
+fn karatsuba_sqr_thousand_cutoff_fifty(bencher: &mut Bencher) {
+    try_karatsuba_sqr(bencher, 50, 1000);
+}
+
+#[bench]
+fn karatsuba_sqr_five_thousand_cutoff_fifty(bencher: &mut Bencher) {
+    try_karatsuba_sqr(bencher, 50, 5000);
+}
+
+fn try_toom3_mult(bencher: &mut Bencher, cutoff: usize, num_nines: usize) {
+    let nines = "9".repeat(num_nines);
+    let a = from_string(&nines).unwrap();
+    let b = a.clone();
+    bencher.iter(|| bignum_toom3_mult(&a, &b, cutoff, 50, false));
+}
+
+fn try_toom4_mult(bencher: &mut Bencher, cutoff: usize, num_nines: usize) {
+    let nines = "9".repeat(num_nines);
+    let a = from_string(&nines).unwrap();
+    let b = a.clone();
+    bencher.iter(|| bignum_toom4_mult(&a, &b, cutoff, 50, false));
+}
+
+#[bench]
+fn karatsuba_fifty_thousand_cutoff_fifty(bencher: &mut Bencher) {
+    try_karatsuba_mult(bencher, 50, 50000, false);
+}
+
+#[bench]
+fn toom3_fifty_thousand_cutoff_six_hundred(bencher: &mut Bencher) {
+    try_toom3_mult(bencher, 600, 50000);
+}
+
+#[bench]
+fn toom4_fifty_thousand_cutoff_twelve_hundred(bencher: &mut Bencher) {
+    try_toom4_mult(bencher, 1200, 50000);
+}
+
+#[bench]
+fn karatsuba_two_hundred_thousand_cutoff_fifty(bencher: &mut Bencher) {
+    try_karatsuba_mult(bencher, 50, 200000, false);
+}
+
+#[bench]
+fn toom3_two_hundred_thousand_cutoff_six_hundred(bencher: &mut Bencher) {
+    try_toom3_mult(bencher, 600, 200000);
+}
+
+#[bench]
+fn toom4_two_hundred_thousand_cutoff_twelve_hundred(bencher: &mut Bencher) {
+    try_toom4_mult(bencher, 1200, 200000);
+}
+
+fn try_ntt_mult(bencher: &mut Bencher, num_nines: usize, parallel: bool) {
+    let nines = "9".repeat(num_nines);
+    let a = from_string(&nines).unwrap();
+    let b = a.clone();
+    bencher.iter(|| bignum_ntt_mult(&a, &b, parallel));
+}
+
+#[bench]
+fn karatsuba_hundred_thousand_cutoff_fifty(bencher: &mut Bencher) {
+    try_karatsuba_mult(bencher, 50, 100000, false);
+}
+
+#[bench]
+fn toom4_hundred_thousand_cutoff_twelve_hundred(bencher: &mut Bencher) {
+    try_toom4_mult(bencher, 1200, 100000);
+}
+
+#[bench]
+fn ntt_five_thousand(bencher: &mut Bencher) {
+    try_ntt_mult(bencher, 5000, false);
+}
+
+#[bench]
+fn ntt_hundred_thousand(bencher: &mut Bencher) {
+    try_ntt_mult(bencher, 100000, false);
+}
+
+#[bench]
+fn ntt_par_hundred_thousand(bencher: &mut Bencher) {
+    try_ntt_mult(bencher, 100000, true);
+}
+
+#[bench]
+fn ntt_million(bencher: &mut Bencher) {
+    try_ntt_mult(bencher, 1000000, false);
+}
+
+#[bench]
+fn ntt_par_million(bencher: &mut Bencher) {
+    try_ntt_mult(bencher, 1000000, true);
+}
+
+fn try_mul(bencher: &mut Bencher, num_nines: usize) {
+    let nines = "9".repeat(num_nines);
+    let a = from_string(&nines).unwrap();
+    let b = a.clone();
+    bencher.iter(|| bignum_mul(&a, &b));
+}
+
+#[bench]
+fn mul_thousand(bencher: &mut Bencher) {
+    try_mul(bencher, 1000);
+}
+
+#[bench]
+fn mul_hundred_thousand(bencher: &mut Bencher) {
+    try_mul(bencher, 100000);
+}
+
+// A long_nines-digit number times a short_nines-digit one
+fn try_lopsided_mult<F>(bencher: &mut Bencher, mult: F, long_nines: usize, short_nines: usize)
+    where F: Fn(&Bignum, &Bignum) -> Bignum {
+    let a = from_string(&"9".repeat(long_nines)).unwrap();
+    let b = from_string(&"9".repeat(short_nines)).unwrap();
+    bencher.iter(|| mult(&a, &b));
+}
+
+#[bench]
+fn long_mult_hundred_thousand_by_six_hundred(bencher: &mut Bencher) {
+    try_lopsided_mult(bencher, |a, b| bignum_long_mult(a, b), 100000, 600);
+}
+
+#[bench]
+fn karatsuba_hundred_thousand_by_six_hundred(bencher: &mut Bencher) {
+    try_lopsided_mult(bencher, |a, b| bignum_karatsuba_mult(a, b, 50, false), 100000, 600);
+}
+
+#[bench]
+fn long_mult_hundred_thousand_by_five_thousand(bencher: &mut Bencher) {
+    try_lopsided_mult(bencher, |a, b| bignum_long_mult(a, b), 100000, 5000);
+}
+
+#[bench]
+fn karatsuba_hundred_thousand_by_five_thousand(bencher: &mut Bencher) {
+    try_lopsided_mult(bencher, |a, b| bignum_karatsuba_mult(a, b, 50, false), 100000, 5000);
+}
+
+#[bench]
+fn karatsuba_million_by_twenty_thousand(bencher: &mut Bencher) {
+    try_lopsided_mult(bencher, |a, b| bignum_karatsuba_mult(a, b, 50, false), 1000000, 20000);
+}
+
+#[bench]
+fn toom4_million_by_twenty_thousand(bencher: &mut Bencher) {
+    try_lopsided_mult(bencher, |a, b| bignum_toom4_mult(a, b, 1200, 50, false), 1000000, 20000);
+}
+
+#[bench]
+fn ntt_million_by_twenty_thousand(bencher: &mut Bencher) {
+    try_lopsided_mult(bencher, |a, b| bignum_ntt_mult(a, b, false), 1000000, 20000);
+}
+
+#[bench]
+fn mul_million_by_twenty_thousand(bencher: &mut Bencher) {
+    try_lopsided_mult(bencher, |a, b| bignum_mul(a, b), 1000000, 20000);
+}
@@ -1,18 +1,14 @@
 use types::*;
 use types::Sign::*;
-use std::cmp;
+use biguint::*;
+use limbs;
 use std::cmp::Ordering;
 
 // TODO: Trim leading zeroes in intermediate forms?
 
 impl Bignum {
     fn is_zero(&self) -> bool {
-        for p in &self.parts {
-            if *p != 0 {
-                return false;
-            }
-        }
-        return true;
+        self.magnitude.parts.is_empty()
     }
     
     fn cmp_sign(&self, other: &Bignum) -> Ordering {
@@ -20,38 +16,14 @@ impl Bignum {
     }
 
     fn cmp_parts(&self, other: &Bignum) -> Ordering {
-        let (left, right) = match self.sign {
-            Nonnegative => (Ordering::Greater, Ordering::Less),
-            Negative => (Ordering::Less, Ordering::Greater),
-        };
-    
-
-        let p = self.parts.len();
-        let q = other.parts.len();
-
-        // Assume no leading zeroes
-        if p > q {
-            return left;
-        } else if p < q {
-            return right;
+        match self.sign {
+            Nonnegative => self.cmp_magnitude(other),
+            Negative => self.cmp_magnitude(other).reverse(),
         }
+    }
 
-        // Same number of digits
-        let self_digits = self.parts.iter();
-        let other_digits = other.parts.iter();
-        let mut zipped = self_digits.zip(other_digits).rev();
-    
-        loop {
-            match zipped.next() {
-                Some((self_digit, other_digit)) =>
-                    if self_digit > other_digit {
-                        return left;
-                    } else if self_digit < other_digit {
-                        return right;
-                    },
-                None => return Ordering::Equal,
-            }
-        }
+    fn cmp_magnitude(&self, other: &Bignum) -> Ordering {
+        limbs::cmp(&self.magnitude.parts, &other.magnitude.parts)
     }
 }
 
@@ -63,10 +35,6 @@ impl PartialOrd for Bignum {
 
 impl Ord for Bignum {
     fn cmp(&self, other: &Bignum) -> Ordering {
-        if self.is_zero() && other.is_zero() {
-            return Ordering::Equal;
-        }
-
         let sign_order = self.cmp_sign(other);
         if sign_order == Ordering::Equal {
             self.cmp_parts(other)
@@ -77,123 +45,204 @@ impl Ord for Bignum {
 }
 
 
-pub fn bignum_add(a: &Bignum, b: &Bignum) -> Bignum {
-    let parts_ord = a.cmp_parts(b);
-    let sign = match (&a.sign, &b.sign, parts_ord) {
-        (&Nonnegative, &Nonnegative, _) => Nonnegative,
-        (&Nonnegative, &Negative, Ordering::Less) => Negative,
-        (&Nonnegative, &Negative, _) => Nonnegative,
-        (&Negative, &Nonnegative, Ordering::Greater) => Negative,
-        (&Negative, &Nonnegative, _) => Nonnegative,
-        (&Negative, &Negative, _) => Nonnegative,
-    };
-
-    let should_sub = a.sign != b.sign;
-    
-    let p = a.parts.len();
-    let q = b.parts.len();
+/// a + b, where either side is a &Bignum or a BignumRef view
+pub fn bignum_add<'a, 'b, A, B>(a: A, b: B) -> Bignum
+    where A: Into<BignumRef<'a>>, B: Into<BignumRef<'b>> {
+    add_views(a.into(), b.into())
+}
 
-    let max = cmp::max(p, q);
-    
-    let mut sum = Bignum {
-        sign: sign,
-        parts: Vec::with_capacity(max + 1),
-    };
+/// a - b, where either side is a &Bignum or a BignumRef view
+pub fn bignum_sub<'a, 'b, A, B>(a: A, b: B) -> Bignum
+    where A: Into<BignumRef<'a>>, B: Into<BignumRef<'b>> {
+    add_views(a.into(), b.into().negated())
+}
 
-    // TODO: make this prettier
-    if !should_sub {
-        let mut carry = 0;
-        for i in 0..max {
-            let a_digit = if i < p {
-                a.parts[i]
-            } else {
-                0
-            };
-            let b_digit = if i < q {
-                b.parts[i]
-            } else {
-                0
-            };
-            let result = a_digit + b_digit + carry;
-            sum.parts.push(result % BASE);
-            carry = result / BASE;
-        }
-        if carry > 0 {
-            sum.parts.push(carry);
-        }
+fn add_views(a: BignumRef, b: BignumRef) -> Bignum {
+    let sum = if a.sign == b.sign {
+        let (big, small) = if a.parts.len() >= b.parts.len() { (a, b) } else { (b, a) };
+        let mut parts = Vec::with_capacity(big.parts.len() + 1);
+        parts.extend_from_slice(big.parts);
+        add_assign_parts(&mut parts, small.parts);
+        make_bignum(a.sign, parts)
     } else {
-        let mut carry = 0;
-        let (big, small) = match a.cmp_parts(b) {
+        // Opposite signs, so subtract the smaller magnitude from the larger
+        let (big, small) = match limbs::cmp(a.parts, b.parts) {
             Ordering::Less => (b, a),
             _ => (a, b),
         };
-        
-        for i in 0..max {
-            let big_digit = if i < big.parts.len() {
-                big.parts[i]
-            } else {
-                0
-            };
-            let small_digit = if i < small.parts.len() {
-                small.parts[i]
-            } else {
-                0
-            };
-
-            let result = if big_digit < (small_digit + carry) {
-                let temp = big_digit + BASE - carry - small_digit;
-                carry = 1;
-                temp
-            } else {
-                let temp = big_digit - carry - small_digit;
-                carry = 0;
-                temp
-            };
-            
-            sum.parts.push(result);
-        }
-    }
+        let mut parts = big.parts.to_vec();
+        sub_assign_parts(&mut parts, small.parts);
+        make_bignum(big.sign, parts)
+    };
 
-    sum.normalize();
+    sum.check_invariants();
     sum
 }
 
-pub fn bignum_sub(a: &Bignum, b: &Bignum) -> Bignum {
-    let neg_b = Bignum {
-        sign: match b.sign {
-            Negative => Nonnegative,
-            Nonnegative => Negative
-        },
-        parts: b.parts.clone(),
-    };
+// Adds parts with the given sign into a, reusing a's buffer
+fn add_parts_in_place(a: &mut Bignum, parts: &[Limb], sign: Sign) {
+    if a.sign == sign {
+        add_assign_parts(&mut a.magnitude.parts, parts);
+    } else if limbs::cmp(&a.magnitude.parts, parts) != Ordering::Less {
+        // |a| - |b|, keeping the sign of a
+        sub_assign_parts(&mut a.magnitude.parts, parts);
+    } else {
+        // |b| - |a|, taking the sign of b
+        sub_from_parts(&mut a.magnitude.parts, parts);
+        a.sign = sign;
+    }
+
+    a.normalize();
+}
+
+pub fn bignum_add_assign(a: &mut Bignum, b: &Bignum) {
+    add_parts_in_place(a, &b.magnitude.parts, b.sign);
+    a.check_invariants();
+}
 
-    bignum_add(a, &neg_b)
-}
-
-pub fn bignum_long_mult(a: &Bignum, b: &Bignum) -> Bignum {
-    // https://en.wikipedia.org/wiki/Multiplication_algorithm#Long_multiplication
-    let p = a.parts.len();
-    let q = b.parts.len();
-    let mut product = Bignum {
-        sign: match (&a.sign, &b.sign) {
-            (&Nonnegative, &Nonnegative) => Nonnegative,
-            (&Nonnegative, &Negative) => Negative,
-            (&Negative, &Nonnegative) => Negative,
-            (&Negative, &Negative) => Nonnegative,
-        },
-        parts: vec!(0; p + q),
+pub fn bignum_sub_assign(a: &mut Bignum, b: &Bignum) {
+    let neg_sign = match b.sign {
+        Negative => Nonnegative,
+        Nonnegative => Negative,
     };
-    
-    for b_i in 0..q {
-        let mut carry: u64 = 0;
-        for a_i in 0..p {
-            let result: u64 = product.parts[a_i + b_i] as u64 + a.parts[a_i] as u64 * b.parts[b_i] as u64 + carry;
-            carry = result / BASE as u64;
-            product.parts[a_i + b_i] = (result % BASE as u64) as u32;
+    add_parts_in_place(a, &b.magnitude.parts, neg_sign);
+    a.check_invariants();
+}
+
+/// a * b by schoolbook multiplication, where either side is a &Bignum or a BignumRef view
+pub fn bignum_long_mult<'a, 'b, A, B>(a: A, b: B) -> Bignum
+    where A: Into<BignumRef<'a>>, B: Into<BignumRef<'b>> {
+    let (a, b) = (a.into(), b.into());
+    let sign = if a.sign == b.sign { Nonnegative } else { Negative };
+    let product = make_bignum(sign, long_mult_parts(a.parts, b.parts));
+    product.check_invariants();
+    product
+}
+
+/// a * a by schoolbook squaring, which computes each cross product only once
+/// bignum_long_mult(&a, &a) ends up here too
+pub fn bignum_sqr<'a, A: Into<BignumRef<'a>>>(a: A) -> Bignum {
+    let square = make_bignum(Nonnegative, sqr_parts(a.into().parts));
+    square.check_invariants();
+    square
+}
+
+// Zero is always Nonnegative, so that it prints as "0" rather than "-0"
+fn make_bignum(sign: Sign, mut parts: Vec<Limb>) -> Bignum {
+    trim_parts(&mut parts);
+    let sign = if parts.is_empty() { Nonnegative } else { sign };
+    Bignum::from_raw(sign, parts)
+}
+
+// Magnitude division, Knuth TAOCP vol. 2, 4.3.1 Algorithm D
+// Expects v to have no leading zeroes and at least one part
+fn divrem_parts(u: &[Limb], v: &[Limb]) -> (Vec<Limb>, Vec<Limb>) {
+    let n = v.len();
+
+    if u.len() < n {
+        return (Vec::new(), u.to_vec());
+    }
+
+    if n == 1 {
+        let mut quotient = u.to_vec();
+        let rem = limbs::divrem_1(&mut quotient, v[0]);
+        return (quotient, vec![rem]);
+    }
+
+    let m = u.len() - n;
+
+    // D1: shift both operands so that the top bit of the divisor is set
+    let shift = v[n - 1].leading_zeros();
+    let mut v = v.to_vec();
+    let mut u = u.to_vec();
+    limbs::lshift(&mut v, shift);
+    let top = limbs::lshift(&mut u, shift);
+    u.push(top);
+
+    let v_top = v[n - 1] as DoubleLimb;
+    let v_next = v[n - 2] as DoubleLimb;
+    let mut quotient = vec![0; m + 1];
+
+    for j in (0..m + 1).rev() {
+        // D3: estimate the quotient part from the top two parts of the remainder
+        let numerator = ((u[j + n] as DoubleLimb) << LIMB_BITS) | u[j + n - 1] as DoubleLimb;
+        let mut q_hat = numerator / v_top;
+        let mut r_hat = numerator % v_top;
+
+        while q_hat >= BASE || q_hat * v_next > (r_hat << LIMB_BITS) + u[j + n - 2] as DoubleLimb {
+            q_hat -= 1;
+            r_hat += v_top;
+            if r_hat >= BASE {
+                break;
+            }
         }
-        product.parts[b_i + p] += carry as u32;
+
+        // D4: multiply and subtract, borrowing from the top part of the remainder
+        let borrow = limbs::submul_1(&mut u[j..j + n], &v, q_hat as Limb);
+        let (top, borrowed) = u[j + n].overflowing_sub(borrow);
+        u[j + n] = top;
+
+        if borrowed {
+            // D6: the estimate was one too large, so add the divisor back
+            q_hat -= 1;
+            let carry = limbs::add_n(&mut u[j..j + n], &v);
+            u[j + n] = u[j + n].wrapping_add(carry as Limb);
+        }
+
+        quotient[j] = q_hat as Limb;
     }
 
-    product.normalize();
-    product
+    // D8: unshift the remainder
+    let mut remainder = u[0..n].to_vec();
+    limbs::rshift(&mut remainder, shift);
+    (quotient, remainder)
+}
+
+/// Truncated division: the quotient is rounded toward zero and
+/// the remainder takes the sign of the dividend
+pub fn bignum_divrem(a: &Bignum, b: &Bignum) -> Result<(Bignum, Bignum), DivideByZeroError> {
+    if b.is_zero() {
+        return Err(DivideByZeroError);
+    }
+
+    let mut divisor = b.magnitude.parts.clone();
+    trim_parts(&mut divisor);
+    let (quotient, remainder) = divrem_parts(&a.magnitude.parts, &divisor);
+
+    let quotient_sign = if a.sign == b.sign { Nonnegative } else { Negative };
+    let quotient = make_bignum(quotient_sign, quotient);
+    let remainder = make_bignum(a.sign, remainder);
+    quotient.check_invariants();
+    remainder.check_invariants();
+    Ok((quotient, remainder))
+}
+
+/// Floored division: the quotient is rounded toward negative infinity and
+/// the remainder takes the sign of the divisor
+pub fn bignum_divrem_floor(a: &Bignum, b: &Bignum) -> Result<(Bignum, Bignum), DivideByZeroError> {
+    let (quotient, remainder) = bignum_divrem(a, b)?;
+
+    if !remainder.is_zero() && a.sign != b.sign {
+        let one = Bignum::from_raw(Nonnegative, vec![1]);
+        Ok((bignum_sub(&quotient, &one), bignum_add(&remainder, b)))
+    } else {
+        Ok((quotient, remainder))
+    }
+}
+
+/// Euclidean division: the remainder is always nonnegative
+pub fn bignum_divrem_euclid(a: &Bignum, b: &Bignum) -> Result<(Bignum, Bignum), DivideByZeroError> {
+    let (quotient, remainder) = bignum_divrem(a, b)?;
+
+    if remainder.sign == Negative {
+        let one = Bignum::from_raw(Nonnegative, vec![1]);
+        let abs_b = Bignum::from_raw(Nonnegative, b.magnitude.parts.clone());
+        let adjusted = match b.sign {
+            Nonnegative => bignum_sub(&quotient, &one),
+            Negative => bignum_add(&quotient, &one),
+        };
+        Ok((adjusted, bignum_add(&remainder, &abs_b)))
+    } else {
+        Ok((quotient, remainder))
+    }
 }
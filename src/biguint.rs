@@ -0,0 +1,214 @@
+use types::*;
+use limbs;
+use decimal::parts_to_decimal;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ptr;
+
+// Unsigned magnitudes, and the carry-propagating loops shared with Bignum
+// Bignum is a BigUint with a sign, so everything here works on plain parts
+// and the signed versions in basic_ops only decide which of these to call
+
+// Same invariant as Bignum: the most significant part is never zero
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct BigUint {
+    pub(crate) parts: Vec<Limb>, // Least significant digit at leftmost index
+}
+
+impl BigUint {
+    /// Builds a BigUint from limbs, least significant first, checking that the top limb is nonzero
+    pub fn from_parts(limbs: Vec<Limb>) -> Result<BigUint, InvalidLimbError> {
+        if limbs.last() == Some(&0) {
+            return Err(InvalidLimbError::LeadingZero { index: limbs.len() - 1 });
+        }
+        Ok(BigUint { parts: limbs })
+    }
+
+    /// Like from_parts, but the invariant is only checked in debug builds
+    pub fn from_parts_unchecked(limbs: Vec<Limb>) -> BigUint {
+        let result = BigUint { parts: limbs };
+        result.check_invariants();
+        result
+    }
+
+    /// The limbs, least significant first, with no leading zero limbs
+    pub fn limbs(&self) -> &[Limb] {
+        &self.parts
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.parts.is_empty()
+    }
+
+    // The same limbs seen as a nonnegative Bignum
+    pub(crate) fn view(&self) -> BignumRef<'_> {
+        BignumRef { parts: &self.parts, sign: Sign::Nonnegative }
+    }
+
+    pub(crate) fn normalize(&mut self) {
+        trim_parts(&mut self.parts);
+    }
+
+    pub(crate) fn check_invariants(&self) {
+        debug_assert!(self.parts.last() != Some(&0), "bignum has a leading zero limb");
+    }
+}
+
+pub(crate) fn trim_parts(parts: &mut Vec<Limb>) {
+    while parts.last() == Some(&0) {
+        parts.pop();
+    }
+}
+
+// a += b
+pub(crate) fn add_assign_parts(a: &mut Vec<Limb>, b: &[Limb]) {
+    if a.len() < b.len() {
+        a.resize(b.len(), 0);
+    }
+    if limbs::add(a, b) {
+        a.push(1);
+    }
+    trim_parts(a);
+}
+
+// a -= b, where a >= b
+pub(crate) fn sub_assign_parts(a: &mut Vec<Limb>, b: &[Limb]) {
+    limbs::sub(a, &b[..limbs::significant_len(b)]);
+    trim_parts(a);
+}
+
+// a = b - a, where b >= a
+pub(crate) fn sub_from_parts(a: &mut Vec<Limb>, b: &[Limb]) {
+    if a.len() < b.len() {
+        a.resize(b.len(), 0);
+    }
+
+    // a - b wraps around to BASE^n - (b - a), and negating that leaves b - a
+    limbs::sub(a, b);
+    for part in a.iter_mut() {
+        *part = !*part;
+    }
+    limbs::add_1(a, 1);
+    trim_parts(a);
+}
+
+// out = a * b, where out has exactly a.len() + b.len() parts
+// Multiplying a slice by itself squares it instead
+pub(crate) fn long_mult_into(a: &[Limb], b: &[Limb], out: &mut [Limb]) {
+    // https://en.wikipedia.org/wiki/Multiplication_algorithm#Long_multiplication
+    let p = a.len();
+    debug_assert_eq!(out.len(), p + b.len());
+
+    if ptr::eq(a, b) {
+        sqr_into(a, out);
+        return;
+    }
+
+    for part in out.iter_mut() {
+        *part = 0;
+    }
+    for (b_i, b_digit) in b.iter().enumerate() {
+        out[b_i + p] = limbs::addmul_1(&mut out[b_i..b_i + p], a, *b_digit);
+    }
+}
+
+// out = a * a, where out has exactly twice as many parts as a
+// Each product a_i * a_j with i < j turns up twice in the square, so they are
+// summed once and doubled, and the squares a_i * a_i go on top
+pub(crate) fn sqr_into(a: &[Limb], out: &mut [Limb]) {
+    let n = a.len();
+    debug_assert_eq!(out.len(), 2 * n);
+
+    for part in out.iter_mut() {
+        *part = 0;
+    }
+    for (i, a_digit) in a.iter().enumerate() {
+        out[i + n] = limbs::addmul_1(&mut out[2 * i + 1..i + n], &a[i + 1..], *a_digit);
+    }
+
+    // The cross products sum to less than half the square, so doubling never carries out
+    limbs::lshift(out, 1);
+
+    let mut carry: DoubleLimb = 0;
+    for (i, a_digit) in a.iter().enumerate() {
+        let square = *a_digit as DoubleLimb * *a_digit as DoubleLimb;
+        let low = out[2 * i] as DoubleLimb + (square as Limb) as DoubleLimb + carry;
+        let high = out[2 * i + 1] as DoubleLimb + (square >> LIMB_BITS) + (low >> LIMB_BITS);
+        out[2 * i] = low as Limb;
+        out[2 * i + 1] = high as Limb;
+        carry = high >> LIMB_BITS;
+    }
+}
+
+pub(crate) fn sqr_parts(a: &[Limb]) -> Vec<Limb> {
+    let mut square = vec![0; 2 * a.len()];
+    sqr_into(a, &mut square);
+    trim_parts(&mut square);
+    square
+}
+
+pub(crate) fn long_mult_parts(a: &[Limb], b: &[Limb]) -> Vec<Limb> {
+    let mut product = vec![0; a.len() + b.len()];
+    long_mult_into(a, b, &mut product);
+    trim_parts(&mut product);
+    product
+}
+
+pub fn biguint_add(a: &BigUint, b: &BigUint) -> BigUint {
+    let (big, small) = if a.parts.len() >= b.parts.len() { (a, b) } else { (b, a) };
+    let mut parts = Vec::with_capacity(big.parts.len() + 1);
+    parts.extend_from_slice(&big.parts);
+    add_assign_parts(&mut parts, &small.parts);
+
+    let sum = BigUint { parts };
+    sum.check_invariants();
+    sum
+}
+
+/// a - b, or an error when b is the larger
+pub fn biguint_sub(a: &BigUint, b: &BigUint) -> Result<BigUint, UnderflowError> {
+    if limbs::cmp(&a.parts, &b.parts) == Ordering::Less {
+        return Err(UnderflowError);
+    }
+
+    let mut difference = a.clone();
+    sub_assign_parts(&mut difference.parts, &b.parts);
+    difference.check_invariants();
+    Ok(difference)
+}
+
+pub fn biguint_long_mult(a: &BigUint, b: &BigUint) -> BigUint {
+    let product = BigUint { parts: long_mult_parts(&a.parts, &b.parts) };
+    product.check_invariants();
+    product
+}
+
+pub fn biguint_sqr(a: &BigUint) -> BigUint {
+    let square = BigUint { parts: sqr_parts(&a.parts) };
+    square.check_invariants();
+    square
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &BigUint) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &BigUint) -> Ordering {
+        limbs::cmp(&self.parts, &other.parts)
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(true, "", &parts_to_decimal(&self.parts, false))
+    }
+}
+
+impl fmt::Debug for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "BigUint({})", self)
+    }
+}
@@ -0,0 +1,188 @@
+extern crate bignum;
+
+use bignum::types::*;
+use bignum::mul::*;
+use std::cmp;
+use std::env;
+use std::fs;
+use std::hint::black_box;
+use std::process;
+use std::time::{Duration, Instant};
+
+// Measures this machine's multiplication thresholds for bignum_mul
+// Each algorithm in turn is timed against the ones already tuned, at a range
+// of sizes, with its threshold set just below that size so that it does one
+// level of its own splitting before handing over. Its threshold goes at the
+// last size where it still lost, once it has won at two sizes in a row
+// The result is written in the format MulConfig parses, or with --rust as a
+// constant to include!
+
+const USAGE: &str = "usage: bignum-tune [--quick] [--rust] [--output PATH]";
+
+// A threshold that is never reached, for the algorithms not tuned yet
+const NEVER: usize = usize::MAX;
+
+struct Options {
+    quick: bool,
+    rust: bool,
+    output: Option<String>,
+}
+
+fn parse_args() -> Options {
+    let mut options = Options { quick: false, rust: false, output: None };
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--quick" => options.quick = true,
+            "--rust" => options.rust = true,
+            "--output" => match args.next() {
+                Some(path) => options.output = Some(path),
+                None => usage_error(),
+            },
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            _ => usage_error(),
+        }
+    }
+    options
+}
+
+fn usage_error() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2);
+}
+
+// Pseudorandom limbs from an xorshift generator, so every run times the same operands
+fn operand(len: usize, seed: u64) -> Bignum {
+    let mut state = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+    let mut limbs: Vec<Limb> = (0..len).map(|_| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state as Limb
+    }).collect();
+    if let Some(top) = limbs.last_mut() {
+        *top |= 1;
+    }
+    Bignum::from_parts(Sign::Nonnegative, limbs).unwrap()
+}
+
+// The best of three rounds of multiplying for the given time, in nanoseconds per multiplication
+fn time(a: &Bignum, b: &Bignum, config: &MulConfig, budget: Duration) -> f64 {
+    let mut best = f64::INFINITY;
+    for _ in 0..3 {
+        let start = Instant::now();
+        let mut runs = 0;
+        while runs == 0 || start.elapsed() < budget {
+            black_box(bignum_mul_with(black_box(a), black_box(b), config));
+            runs += 1;
+        }
+        best = best.min(start.elapsed().as_nanos() as f64 / runs as f64);
+    }
+    best
+}
+
+// The threshold for the algorithm that config_at(threshold) enables on top of base,
+// searching sizes from start up to end, or end if it never wins
+// The config tried at each size enables the algorithm from one part below it,
+// so start has to be two above the threshold before it for that config to be valid
+fn crossover<F>(name: &str, start: usize, end: usize, options: &Options, base: MulConfig, config_at: F) -> usize
+    where F: Fn(usize) -> MulConfig {
+    let end = cmp::max(start, end);
+    let budget = Duration::from_millis(if options.quick { 10 } else { 50 });
+    let step = if options.quick { 2 } else { 4 };
+
+    let mut last_loss = start;
+    let mut wins = 0;
+    let mut size = start;
+    while size <= end {
+        let a = operand(size, 1);
+        let b = operand(size, 2);
+        let before = time(&a, &b, &base, budget);
+        let after = time(&a, &b, &config_at(size - 1), budget);
+        eprintln!("{:>9} {:>7} parts: {:>13.0} ns without, {:>13.0} ns with", name, size, before, after);
+
+        if after < before {
+            wins += 1;
+            if wins == 2 {
+                return last_loss;
+            }
+        } else {
+            wins = 0;
+            last_loss = size;
+        }
+        size += cmp::max(size / step, 1);
+    }
+
+    eprintln!("{:>9} never won up to {} parts", name, end);
+    end
+}
+
+fn tune(options: &Options) -> MulConfig {
+    let scale = if options.quick { 1 } else { 2 };
+    let schoolbook = MulConfig {
+        karatsuba_threshold: NEVER,
+        toom3_threshold: NEVER,
+        toom4_threshold: NEVER,
+        ntt_threshold: NEVER,
+        parallel: false,
+        ..MulConfig::DEFAULT
+    };
+
+    let karatsuba = crossover("karatsuba", 8, 256 * scale, options, schoolbook,
+                              |t| MulConfig { karatsuba_threshold: t, ..schoolbook });
+    let base = MulConfig { karatsuba_threshold: karatsuba, ..schoolbook };
+
+    let toom3 = crossover("toom3", 4 * karatsuba, 5000 * scale, options, base,
+                          |t| MulConfig { toom3_threshold: t, ..base });
+    let base = MulConfig { toom3_threshold: toom3, ..base };
+
+    let toom4 = crossover("toom4", toom3 + 2, 10000 * scale, options, base,
+                          |t| MulConfig { toom4_threshold: t, ..base });
+    let base = MulConfig { toom4_threshold: toom4, ..base };
+
+    let ntt = crossover("ntt", toom4 + 2, 30000 * scale, options, base,
+                        |t| MulConfig { ntt_threshold: t, ..base });
+    MulConfig { ntt_threshold: ntt, ..base }
+}
+
+fn render(config: &MulConfig, rust: bool) -> String {
+    if rust {
+        format!("// Multiplication thresholds for this machine, measured by bignum-tune\n\
+                 pub const TUNED_MUL_CONFIG: ::bignum::mul::MulConfig = ::bignum::mul::MulConfig {{\n    \
+                 karatsuba_threshold: {},\n    \
+                 toom3_threshold: {},\n    \
+                 toom4_threshold: {},\n    \
+                 ntt_threshold: {},\n    \
+                 parallel: {},\n    \
+                 parallel_depth: {},\n    \
+                 parallel_grain: {},\n\
+                 }};\n",
+                config.karatsuba_threshold, config.toom3_threshold, config.toom4_threshold,
+                config.ntt_threshold, config.parallel, config.parallel_depth, config.parallel_grain)
+    } else {
+        format!("# Multiplication thresholds for this machine, measured by bignum-tune\n{}", config)
+    }
+}
+
+fn main() {
+    let options = parse_args();
+    let config = tune(&options);
+
+    // Each search starts above the threshold before it, so this only fails on a bug here
+    if let Err(e) = config.validate() {
+        eprintln!("tuned config is invalid: {}", e);
+        process::exit(1);
+    }
+    let rendered = render(&config, options.rust);
+
+    match options.output {
+        Some(ref path) => fs::write(path, rendered).unwrap_or_else(|e| {
+            eprintln!("cannot write {}: {}", path, e);
+            process::exit(1);
+        }),
+        None => print!("{}", rendered),
+    }
+}
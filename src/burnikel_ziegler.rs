@@ -0,0 +1,154 @@
+use types::*;
+use types::Sign::*;
+use basic_ops::*;
+use karatsuba::*;
+use mul::*;
+use helpers::*;
+use parallel::*;
+use std::cmp;
+use std::cmp::Ordering;
+
+// Recursive division, following Burnikel and Ziegler,
+// "Fast Recursive Division" (MPI-I-98-1-022)
+// All intermediate values here are nonnegative apart from the remainder
+// in div_3n_2n, which may briefly go below zero before being corrected
+
+struct Params {
+    cutoff: usize,
+    parallel: Parallelism,
+    // The multiplications go through bignum_mul_with under this config when
+    // there is one, and are Karatsuba multiplications with cutoff otherwise
+    config: Option<MulConfig>,
+}
+
+pub fn bignum_burnikel_ziegler_divrem<P>(a: &Bignum, b: &Bignum, cutoff: usize, parallel: P)
+                                         -> Result<(Bignum, Bignum), DivideByZeroError>
+    where P: Into<Parallelism> {
+    divrem(a, b, Params { cutoff, parallel: parallel.into(), config: None })
+}
+
+/// Burnikel-Ziegler division whose multiplications go through bignum_mul_with
+/// under config, so that large ones use Toom-Cook or the NTT
+pub fn bignum_burnikel_ziegler_divrem_with(a: &Bignum, b: &Bignum, cutoff: usize, config: &MulConfig)
+                                           -> Result<(Bignum, Bignum), DivideByZeroError> {
+    divrem(a, b, Params { cutoff, parallel: config.parallelism(), config: Some(*config) })
+}
+
+fn divrem(a: &Bignum, b: &Bignum, params: Params) -> Result<(Bignum, Bignum), DivideByZeroError> {
+    assert!(params.cutoff >= 2);
+
+    let a_abs = magnitude(a);
+    let b_abs = magnitude(b);
+
+    if b_abs.magnitude.parts.is_empty() {
+        return Err(DivideByZeroError);
+    }
+
+    if b_abs.magnitude.parts.len() <= params.cutoff || a_abs.magnitude.parts.len() < b_abs.magnitude.parts.len() {
+        return bignum_divrem(a, b);
+    }
+
+    let (quotient, remainder) = divrem_magnitude(&a_abs, &b_abs, &params);
+
+    let quotient_sign = if a.sign == b.sign { Nonnegative } else { Negative };
+    let quotient = with_sign(quotient, quotient_sign);
+    let remainder = with_sign(remainder, a.sign);
+    quotient.check_invariants();
+    remainder.check_invariants();
+    Ok((quotient, remainder))
+}
+
+fn mult(a: &Bignum, b: &Bignum, params: &Params) -> Bignum {
+    match params.config {
+        Some(ref config) => bignum_mul_with(a, b, config),
+        None => bignum_karatsuba_mult(a, b, params.cutoff, params.parallel),
+    }
+}
+
+fn divrem_magnitude(a: &Bignum, b: &Bignum, params: &Params) -> (Bignum, Bignum) {
+    let b_len = b.magnitude.parts.len();
+
+    // Pad the divisor up to n = j * 2^k parts with j <= cutoff,
+    // so that the recursion halves evenly all the way down to the base case
+    let mut j = b_len;
+    let mut k = 0;
+    while j > params.cutoff {
+        j = j.div_ceil(2);
+        k += 1;
+    }
+    let n = j << k;
+    let padding = n - b_len;
+
+    // Scale by a power of two so that the top bit of the divisor is set
+    let scale = Bignum::from_raw(Nonnegative, vec![1 << b.magnitude.parts[b_len - 1].leading_zeros()]);
+    let b_norm = shifted(&trimmed(bignum_long_mult(b, &scale)), padding);
+    let a_norm = shifted(&trimmed(bignum_long_mult(a, &scale)), padding);
+
+    // Split the dividend into t blocks of n parts, where the top block is smaller than the divisor
+    let t = cmp::max(2, (a_norm.magnitude.parts.len() + n) / n);
+
+    let mut z = high(&a_norm, (t - 2) * n);
+    let mut quotient = Bignum::from_raw(Nonnegative, Vec::new());
+    let mut remainder = Bignum::from_raw(Nonnegative, Vec::new());
+
+    for i in (0..t - 1).rev() {
+        let (q_i, r_i) = div_2n_1n(&z, &b_norm, n, params);
+        quotient = shifted(&quotient, n) + &q_i;
+
+        if i > 0 {
+            let block = low(&high(&a_norm, (i - 1) * n), n);
+            z = shifted(&r_i, n) + &block;
+        } else {
+            remainder = r_i;
+        }
+    }
+
+    // Undo the normalization; the padding parts of the remainder are all zero
+    let (remainder, _) = bignum_divrem(&high(&remainder, padding), &scale)
+        .expect("scale is nonzero");
+    (quotient, trimmed(remainder))
+}
+
+// Divides a by b where b has n parts and a < b * BASE^n
+fn div_2n_1n(a: &Bignum, b: &Bignum, n: usize, params: &Params) -> (Bignum, Bignum) {
+    if n % 2 == 1 || n <= params.cutoff {
+        let (q, r) = bignum_divrem(a, b).expect("divisor is nonzero");
+        return (trimmed(q), trimmed(r));
+    }
+
+    let half = n / 2;
+    let (q_1, r) = div_3n_2n(&high(a, half), b, half, params);
+    let (q_2, s) = div_3n_2n(&(shifted(&r, half) + &low(a, half)), b, half, params);
+
+    (shifted(&q_1, half) + &q_2, s)
+}
+
+// Divides a by b where b has 2 * half parts and a < b * BASE^half
+fn div_3n_2n(a: &Bignum, b: &Bignum, half: usize, params: &Params) -> (Bignum, Bignum) {
+    let b_1 = high(b, half);
+    let b_2 = low(b, half);
+    let a_12 = high(a, half);
+    let a_1 = high(a, half * 2);
+    let a_3 = low(a, half);
+
+    let (mut q, r_1) = if a_1.cmp(&b_1) == Ordering::Less {
+        div_2n_1n(&a_12, &b_1, half, params)
+    } else {
+        // The quotient estimate saturates at BASE^half - 1
+        let q = Bignum::from_raw(Nonnegative, vec![Limb::MAX; half]);
+        let r_1 = &a_12 - shifted(&b_1, half) + &b_1;
+        (q, r_1)
+    };
+
+    let d = mult(&q, &b_2, params);
+    let mut r = shifted(&r_1, half) + &a_3 - &d;
+
+    // Since b is normalized, this runs at most twice
+    let one = Bignum::from_raw(Nonnegative, vec![1]);
+    while is_negative(&r) {
+        r += b;
+        q -= &one;
+    }
+
+    (q, r)
+}
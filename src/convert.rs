@@ -0,0 +1,157 @@
+use types::*;
+use types::Sign::*;
+use biguint::BigUint;
+use std::convert::TryFrom;
+
+// Conversions between Bignum, BigUint and the primitive integer types
+// Everything goes through a u128 magnitude, which is as wide as any primitive
+
+const PARTS_IN_U128: usize = 128 / LIMB_BITS as usize;
+
+fn from_magnitude(mut n: u128, sign: Sign) -> Bignum {
+    let mut parts = Vec::with_capacity(PARTS_IN_U128);
+    while n > 0 {
+        parts.push(n as Limb);
+        n >>= LIMB_BITS;
+    }
+    let sign = if parts.is_empty() { Nonnegative } else { sign };
+    let result = Bignum::from_raw(sign, parts);
+    result.check_invariants();
+    result
+}
+
+fn significant_parts(a: &Bignum) -> &[Limb] {
+    let mut len = a.magnitude.parts.len();
+    while len > 0 && a.magnitude.parts[len - 1] == 0 {
+        len -= 1;
+    }
+    &a.magnitude.parts[..len]
+}
+
+// The lowest 128 bits of the magnitude
+fn low_magnitude(parts: &[Limb]) -> u128 {
+    parts.iter().take(PARTS_IN_U128).rev()
+        .fold(0, |acc, part| (acc << LIMB_BITS) | *part as u128)
+}
+
+// The magnitude, or None if it does not fit in a u128
+fn magnitude(a: &Bignum) -> Option<(u128, bool)> {
+    let parts = significant_parts(a);
+    if parts.len() > PARTS_IN_U128 {
+        return None;
+    }
+    Some((low_magnitude(parts), a.sign == Negative && !parts.is_empty()))
+}
+
+// The value modulo 2^128, in two's complement
+fn wrapping_u128(a: &Bignum) -> u128 {
+    let parts = significant_parts(a);
+    let low = low_magnitude(parts);
+    if a.sign == Negative { low.wrapping_neg() } else { low }
+}
+
+impl From<BigUint> for Bignum {
+    fn from(n: BigUint) -> Bignum {
+        Bignum::from_sign_magnitude(Nonnegative, n)
+    }
+}
+
+impl From<&BigUint> for Bignum {
+    fn from(n: &BigUint) -> Bignum {
+        Bignum::from_sign_magnitude(Nonnegative, n.clone())
+    }
+}
+
+impl TryFrom<Bignum> for BigUint {
+    type Error = TryFromBignumError;
+
+    fn try_from(a: Bignum) -> Result<BigUint, TryFromBignumError> {
+        match a.into_sign_magnitude() {
+            (Negative, _) => Err(TryFromBignumError),
+            (Nonnegative, magnitude) => Ok(magnitude),
+        }
+    }
+}
+
+impl TryFrom<&Bignum> for BigUint {
+    type Error = TryFromBignumError;
+
+    fn try_from(a: &Bignum) -> Result<BigUint, TryFromBignumError> {
+        match a.sign {
+            Negative => Err(TryFromBignumError),
+            Nonnegative => Ok(a.magnitude.clone()),
+        }
+    }
+}
+
+macro_rules! impl_unsigned {
+    ($($t:ty, $wrapping:ident);*) => {$(
+        impl From<$t> for Bignum {
+            fn from(n: $t) -> Bignum {
+                from_magnitude(n as u128, Nonnegative)
+            }
+        }
+
+        impl From<$t> for BigUint {
+            fn from(n: $t) -> BigUint {
+                from_magnitude(n as u128, Nonnegative).magnitude
+            }
+        }
+
+        impl<'a> TryFrom<&'a Bignum> for $t {
+            type Error = TryFromBignumError;
+
+            fn try_from(a: &Bignum) -> Result<$t, TryFromBignumError> {
+                match magnitude(a) {
+                    Some((n, false)) => <$t>::try_from(n).map_err(|_| TryFromBignumError),
+                    _ => Err(TryFromBignumError),
+                }
+            }
+        }
+
+        impl Bignum {
+            /// The value modulo 2^N for an N-bit target, like an `as` cast
+            pub fn $wrapping(&self) -> $t {
+                wrapping_u128(self) as $t
+            }
+        }
+    )*}
+}
+
+macro_rules! impl_signed {
+    ($($t:ty, $wrapping:ident);*) => {$(
+        impl From<$t> for Bignum {
+            fn from(n: $t) -> Bignum {
+                let sign = if n < 0 { Negative } else { Nonnegative };
+                from_magnitude(n.unsigned_abs() as u128, sign)
+            }
+        }
+
+        impl<'a> TryFrom<&'a Bignum> for $t {
+            type Error = TryFromBignumError;
+
+            fn try_from(a: &Bignum) -> Result<$t, TryFromBignumError> {
+                match magnitude(a) {
+                    Some((n, false)) => <$t>::try_from(n).map_err(|_| TryFromBignumError),
+                    // The negative range reaches one further than the positive one
+                    Some((n, true)) if n <= <$t>::MIN.unsigned_abs() as u128 => {
+                        Ok((n as i128).wrapping_neg() as $t)
+                    }
+                    _ => Err(TryFromBignumError),
+                }
+            }
+        }
+
+        impl Bignum {
+            /// The value modulo 2^N for an N-bit target, like an `as` cast
+            pub fn $wrapping(&self) -> $t {
+                wrapping_u128(self) as $t
+            }
+        }
+    )*}
+}
+
+impl_unsigned!(u8, to_u8_wrapping; u16, to_u16_wrapping; u32, to_u32_wrapping;
+               u64, to_u64_wrapping; u128, to_u128_wrapping; usize, to_usize_wrapping);
+impl_signed!(i8, to_i8_wrapping; i16, to_i16_wrapping; i32, to_i32_wrapping;
+             i64, to_i64_wrapping; i128, to_i128_wrapping; isize, to_isize_wrapping);
@@ -0,0 +1,166 @@
+use types::*;
+use types::Sign::*;
+use mul::*;
+use parallel;
+use burnikel_ziegler::*;
+use radix::{magnitude_to_str_radix, pack_chunks, parse_radix};
+use std::sync::{Arc, Mutex};
+
+// Divide-and-conquer conversion between Bignum and decimal strings
+// Both directions split the number at 10^(9 * 2^i), so that each level
+// does one multiplication or division of half the size of the level above
+
+// Numbers of at most this many parts are converted digit by digit
+const LEAF_PARTS: usize = 32;
+const LEAF_DIGITS: usize = 9 * LEAF_PARTS;
+
+const BURNIKEL_ZIEGLER_CUTOFF: usize = 50;
+
+// Each part holds at most this many decimal digits, rounded up (32 * log10(2))
+const DIGITS_PER_PART: f64 = 9.633;
+
+// The global multiplication thresholds, with parallelism as the caller asked
+fn mul_config(parallel: bool) -> MulConfig {
+    MulConfig { parallel, ..MulConfig::global() }
+}
+
+// POWERS_OF_TEN[i] is 10^(9 * 2^i), extended on demand and shared across calls
+static POWERS_OF_TEN: Mutex<Vec<Arc<Bignum>>> = Mutex::new(Vec::new());
+
+// The lock is never held while squaring, which can fork onto rayon: a worker
+// waiting in that join may pick up another conversion, which would then block
+// on a lock its own thread holds
+fn powers_of_ten(count: usize, parallel: bool) -> Vec<Arc<Bignum>> {
+    loop {
+        let (len, last) = {
+            let mut powers = POWERS_OF_TEN.lock().unwrap_or_else(|e| e.into_inner());
+            if powers.is_empty() {
+                powers.push(Arc::new(Bignum::from_raw(Nonnegative, vec![1000000000])));
+            }
+            if powers.len() >= count {
+                return powers[..count].to_vec();
+            }
+            (powers.len(), powers[powers.len() - 1].clone())
+        };
+
+        let next = bignum_mul_with(last.view(), last.view(), &mul_config(parallel));
+
+        // Another thread may have got there first, in which case its square is the same
+        let mut powers = POWERS_OF_TEN.lock().unwrap_or_else(|e| e.into_inner());
+        if powers.len() == len {
+            powers.push(Arc::new(next));
+        }
+    }
+}
+
+fn digits_in_power(i: usize) -> usize {
+    9 << i
+}
+
+// The largest i for which POWERS_OF_TEN[i] has fewer digits than a number of
+// len digits, or 0 if none does
+fn largest_power_below(len: usize) -> usize {
+    let mut i = 0;
+    while digits_in_power(i + 1) < len {
+        i += 1;
+    }
+    i
+}
+
+fn join<A, B, RA, RB>(parallel: bool, a: A, b: B) -> (RA, RB)
+    where A: FnOnce() -> RA + Send, B: FnOnce() -> RB + Send, RA: Send, RB: Send {
+    if parallel {
+        parallel::join(a, b)
+    } else {
+        (a(), b())
+    }
+}
+
+// Packs decimal digit values, most significant first, into parts
+pub(crate) fn parts_from_decimal(digits: &[u32], parallel: bool) -> Vec<Limb> {
+    if digits.len() <= LEAF_DIGITS {
+        return pack_chunks(digits, 10);
+    }
+
+    let i = largest_power_below(digits.len());
+    let powers = powers_of_ten(i + 1, parallel);
+    from_decimal_rec(digits, &powers, parallel).magnitude.parts
+}
+
+fn from_decimal_rec(digits: &[u32], powers: &[Arc<Bignum>], parallel: bool) -> Bignum {
+    if digits.len() <= LEAF_DIGITS {
+        return Bignum::from_raw(Nonnegative, pack_chunks(digits, 10));
+    }
+
+    // The largest power with fewer digits than the input splits it into
+    // a low half of exactly that many digits and a high half no longer than it
+    let i = largest_power_below(digits.len());
+    let (high_digits, low_digits) = digits.split_at(digits.len() - digits_in_power(i));
+
+    let (high, low) = join(parallel,
+                           || from_decimal_rec(high_digits, powers, parallel),
+                           || from_decimal_rec(low_digits, powers, parallel));
+
+    bignum_mul_with(&high, powers[i].view(), &mul_config(parallel)) + &low
+}
+
+/// Parses a decimal string, like from_string, optionally converting in parallel
+pub fn bignum_from_decimal(s: &str, parallel: bool) -> Result<Bignum, ParseBignumError> {
+    parse_radix(s, 10, false, parallel)
+}
+
+/// Formats a Bignum in decimal, like to_string, optionally converting in parallel
+pub fn bignum_to_decimal(a: &Bignum, parallel: bool) -> String {
+    let digits = parts_to_decimal(&a.magnitude.parts, parallel);
+    if a.sign == Negative && digits != "0" {
+        format!("-{}", digits)
+    } else {
+        digits
+    }
+}
+
+// Decimal digits of the magnitude of parts, without leading zeroes
+pub(crate) fn parts_to_decimal(parts: &[Limb], parallel: bool) -> String {
+    if parts.len() <= LEAF_PARTS {
+        return magnitude_to_str_radix(parts, 10);
+    }
+
+    // Enough levels that the number is below POWERS_OF_TEN[i]^2
+    let max_digits = (parts.len() as f64 * DIGITS_PER_PART).ceil() as usize;
+    let i = largest_power_below(max_digits);
+
+    let powers = powers_of_ten(i + 1, parallel);
+    let a = Bignum::from_raw(Nonnegative, parts.to_vec());
+    to_decimal_rec(&a, i, &powers, 0, parallel)
+}
+
+// The digits of a < POWERS_OF_TEN[i]^2, zero padded to width
+fn to_decimal_rec(a: &Bignum, i: usize, powers: &[Arc<Bignum>], width: usize, parallel: bool) -> String {
+    if a.magnitude.parts.len() <= LEAF_PARTS {
+        let digits = magnitude_to_str_radix(&a.magnitude.parts, 10);
+        let mut padded = String::with_capacity(width);
+        for _ in digits.len()..width {
+            padded.push('0');
+        }
+        padded.push_str(&digits);
+        return padded;
+    }
+
+    // Without padding, a top half of zero would leave the bottom half zero padded
+    if width == 0 && *a < *powers[i] {
+        return to_decimal_rec(a, i - 1, powers, 0, parallel);
+    }
+
+    let (high, low) = bignum_burnikel_ziegler_divrem_with(a, &powers[i], BURNIKEL_ZIEGLER_CUTOFF, &mul_config(parallel))
+        .expect("powers of ten are nonzero");
+
+    let low_width = digits_in_power(i);
+    let high_width = width.saturating_sub(low_width);
+
+    let (mut high_str, low_str) = join(parallel,
+                                       || to_decimal_rec(&high, i - 1, powers, high_width, parallel),
+                                       || to_decimal_rec(&low, i - 1, powers, low_width, parallel));
+
+    high_str.push_str(&low_str);
+    high_str
+}
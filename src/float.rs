@@ -0,0 +1,109 @@
+use types::*;
+use types::Sign::*;
+
+// Conversion to and from floating point
+// Going to a float rounds to nearest, ties to even, going back truncates towards zero
+
+// Bits in an f64 mantissa, not counting the implicit leading one
+const F64_MANTISSA_BITS: u32 = 52;
+const F64_EXPONENT_BIAS: i32 = 1023;
+
+impl Bignum {
+    /// The nearest f64, with ties going to even and infinity once out of range
+    pub fn to_f64(&self) -> f64 {
+        let (top, shift) = top_bits(&self.magnitude.parts);
+        let magnitude = if shift == 0 { top as f64 } else { top as f64 * 2f64.powi(shift) };
+        if self.sign == Negative { -magnitude } else { magnitude }
+    }
+
+    /// The nearest f32, with ties going to even and infinity once out of range
+    pub fn to_f32(&self) -> f32 {
+        let (top, shift) = top_bits(&self.magnitude.parts);
+        let magnitude = if shift == 0 { top as f32 } else { top as f32 * 2f32.powi(shift) };
+        if self.sign == Negative { -magnitude } else { magnitude }
+    }
+
+    /// The integer part of x, rounding towards zero
+    pub fn from_f64(x: f64) -> Result<Bignum, FromFloatError> {
+        if x.is_nan() {
+            return Err(FromFloatError::NaN);
+        }
+        if x.is_infinite() {
+            return Err(FromFloatError::Infinite);
+        }
+
+        // x = mantissa * 2^exponent exactly, subnormals have no implicit bit
+        let bits = x.to_bits();
+        let biased_exponent = ((bits >> F64_MANTISSA_BITS) & 0x7ff) as i32;
+        let mut mantissa = bits & ((1 << F64_MANTISSA_BITS) - 1);
+        let exponent = if biased_exponent == 0 {
+            1 - F64_EXPONENT_BIAS - F64_MANTISSA_BITS as i32
+        } else {
+            mantissa |= 1 << F64_MANTISSA_BITS;
+            biased_exponent - F64_EXPONENT_BIAS - F64_MANTISSA_BITS as i32
+        };
+
+        let mut result = if exponent >= 0 {
+            let places = exponent as usize / LIMB_BITS as usize;
+            let bits = exponent as u32 % LIMB_BITS;
+            let mut result = Bignum::from((mantissa as u128) << bits);
+            if !result.magnitude.parts.is_empty() {
+                shift_left(&mut result, places);
+            }
+            result
+        } else if exponent > -64 {
+            Bignum::from(mantissa >> -exponent)
+        } else {
+            Bignum::from(0u8)
+        };
+
+        if x < 0.0 && !result.magnitude.parts.is_empty() {
+            result.sign = Negative;
+        }
+        result.check_invariants();
+        Ok(result)
+    }
+
+    /// The integer part of x, rounding towards zero
+    pub fn from_f32(x: f32) -> Result<Bignum, FromFloatError> {
+        // Every f32 is exactly representable as an f64
+        Bignum::from_f64(x as f64)
+    }
+}
+
+// The magnitude as top * 2^shift, where top holds the leading 64 bits and
+// its lowest bit is set if any bits below were cut off. That sticky bit lies
+// below the rounding position of either float type, so converting top rounds
+// exactly as converting the whole magnitude would
+fn top_bits(parts: &[Limb]) -> (u64, i32) {
+    let mut len = parts.len();
+    while len > 0 && parts[len - 1] == 0 {
+        len -= 1;
+    }
+    let parts = &parts[..len];
+
+    let bits = len * LIMB_BITS as usize - parts.last().map_or(0, |p| p.leading_zeros() as usize);
+    if bits <= 64 {
+        let value = parts.iter().rev().fold(0, |acc, part| (acc << LIMB_BITS) | *part as u64);
+        return (value, 0);
+    }
+
+    // Bits at and above this index make up top
+    let low_bit = bits - 64;
+    let index = low_bit / LIMB_BITS as usize;
+    let offset = low_bit as u32 % LIMB_BITS;
+
+    let mut window: u128 = 0;
+    for part in parts[index..].iter().take(3).rev() {
+        window = (window << LIMB_BITS) | *part as u128;
+    }
+    let mut top = (window >> offset) as u64;
+
+    let dropped = parts[..index].iter().any(|p| *p != 0) || parts[index] & ((1 << offset) - 1) != 0;
+    if dropped {
+        top |= 1;
+    }
+
+    // Anything this large overflows either float type, so clamping keeps powi in range
+    (top, if low_bit > 2048 { 2048 } else { low_bit as i32 })
+}
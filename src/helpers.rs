@@ -0,0 +1,46 @@
+use types::*;
+use types::Sign::*;
+
+// Helpers shared by the division routines, which work on plain magnitudes
+// split into blocks of parts
+
+pub fn trimmed(mut a: Bignum) -> Bignum {
+    while a.magnitude.parts.last() == Some(&0) {
+        a.magnitude.parts.pop();
+    }
+    a
+}
+
+pub fn magnitude(a: &Bignum) -> Bignum {
+    trimmed(Bignum::from_raw(Nonnegative, a.magnitude.parts.clone()))
+}
+
+pub fn with_sign(mut a: Bignum, sign: Sign) -> Bignum {
+    a = trimmed(a);
+    a.sign = if a.magnitude.parts.is_empty() { Nonnegative } else { sign };
+    a
+}
+
+pub fn is_negative(a: &Bignum) -> bool {
+    a.sign == Negative && !a.magnitude.parts.is_empty()
+}
+
+// The parts below index k
+pub fn low(a: &Bignum, k: usize) -> Bignum {
+    let end = if k < a.magnitude.parts.len() { k } else { a.magnitude.parts.len() };
+    trimmed(Bignum::from_raw(Nonnegative, a.magnitude.parts[..end].to_vec()))
+}
+
+// The parts from index k upwards
+pub fn high(a: &Bignum, k: usize) -> Bignum {
+    let parts = if k < a.magnitude.parts.len() { a.magnitude.parts[k..].to_vec() } else { Vec::new() };
+    Bignum::from_raw(Nonnegative, parts)
+}
+
+pub fn shifted(a: &Bignum, num_places: usize) -> Bignum {
+    let mut result = a.clone();
+    if !result.magnitude.parts.is_empty() {
+        shift_left(&mut result, num_places);
+    }
+    result
+}
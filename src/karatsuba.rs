@@ -1,123 +1,266 @@
-extern crate rayon;
-
 use types::*;
 use types::Sign::*;
-use basic_ops::*;
+use biguint::*;
+use limbs;
+use parallel::*;
 use std::cmp;
+use std::ptr;
+
+/// a * b by Karatsuba multiplication, where either side is a &Bignum or a BignumRef view
+pub fn bignum_karatsuba_mult<'a, 'b, A, B, P>(a: A, b: B, cutoff: usize, parallel: P) -> Bignum
+    where A: Into<BignumRef<'a>>, B: Into<BignumRef<'b>>, P: Into<Parallelism> {
+    let (a, b) = (a.into(), b.into());
+    let sign = if a.sign == b.sign { Nonnegative } else { Negative };
+    let magnitude = BigUint { parts: karatsuba_parts(a.parts, b.parts, cutoff, parallel.into()) };
+    let product = Bignum::from_sign_magnitude(sign, magnitude);
+    product.check_invariants();
+    product
+}
 
-pub fn bignum_karatsuba_mult(a: &Bignum, b: &Bignum, cutoff: usize, parallel: bool) -> Bignum {
-    let sign = match (&a.sign, &b.sign) {
-        (&Nonnegative, &Nonnegative) => Nonnegative,
-        (&Nonnegative, &Negative) => Negative,
-        (&Negative, &Nonnegative) => Negative,
-        (&Negative, &Negative) => Nonnegative,
-    };
+pub fn biguint_karatsuba_mult<P: Into<Parallelism>>(a: &BigUint, b: &BigUint, cutoff: usize, parallel: P) -> BigUint {
+    let product = BigUint { parts: karatsuba_parts(&a.parts, &b.parts, cutoff, parallel.into()) };
+    product.check_invariants();
+    product
+}
 
-    let runner = if parallel { karatsuba_rec_parallel } else { karatsuba_rec };
-    let result = runner(a, b, cutoff);
+/// a * a by Karatsuba squaring, which needs three half-size squarings per level
+/// bignum_karatsuba_mult(&a, &a, ...) ends up here too
+pub fn bignum_karatsuba_sqr<'a, A, P>(a: A, cutoff: usize, parallel: P) -> Bignum
+    where A: Into<BignumRef<'a>>, P: Into<Parallelism> {
+    let a = a.into();
+    let magnitude = BigUint { parts: karatsuba_parts(a.parts, a.parts, cutoff, parallel.into()) };
+    let square = Bignum::from_sign_magnitude(Nonnegative, magnitude);
+    square.check_invariants();
+    square
+}
 
-    Bignum {
-        sign: sign,
-        parts: result.parts,
+pub fn biguint_karatsuba_sqr<P: Into<Parallelism>>(a: &BigUint, cutoff: usize, parallel: P) -> BigUint {
+    let square = BigUint { parts: karatsuba_parts(&a.parts, &a.parts, cutoff, parallel.into()) };
+    square.check_invariants();
+    square
+}
+
+/// The scratch space karatsuba_mult_into needs for operands of up to n limbs,
+/// whatever the cutoff
+pub fn karatsuba_scratch_len(n: usize) -> usize {
+    if n <= 3 {
+        return 0;
     }
+
+    // Each level keeps both half sums and their product, and the recursion
+    // into the half sums is the deepest
+    let m = n.div_ceil(2);
+    4 * (m + 1) + karatsuba_scratch_len(m + 1)
 }
 
-fn divide_round_up(a: usize, b: usize) -> usize {
-    // http://stackoverflow.com/questions/17944/how-to-round-up-the-result-of-integer-division
-    (a - 1) / b + 1
+// The scratch space karatsuba_into needs for operands of up to n parts under
+// parallel, where each level that forks gives its three products a region of
+// their own, side by side, in place of the one they would share
+// Without forking this is karatsuba_scratch_len, and it stays at a few times
+// that for the usual handful of forking levels
+fn parallel_scratch_len(n: usize, parallel: Parallelism) -> usize {
+    if n <= 3 || !parallel.forks(n) {
+        return karatsuba_scratch_len(n);
+    }
+
+    let m = n.div_ceil(2);
+    4 * (m + 1) + 3 * parallel_scratch_len(m + 1, parallel.below())
 }
 
-fn karatsuba_rec(a: &Bignum, b: &Bignum, cutoff: usize) -> Bignum {
+/// out = a * b by Karatsuba multiplication on limb slices, without allocating
+/// out must have exactly a.len() + b.len() limbs and scratch at least
+/// karatsuba_scratch_len of the longer operand; neither needs clearing first
+/// Passing the same slice as a and b squares it
+pub fn karatsuba_mult_into(a: &[Limb], b: &[Limb], out: &mut [Limb], scratch: &mut [Limb], cutoff: usize) {
+    assert!(cutoff >= 2);
+    assert_eq!(out.len(), a.len() + b.len(), "output slice has the wrong length");
+    assert!(scratch.len() >= karatsuba_scratch_len(cmp::max(a.len(), b.len())), "scratch slice is too short");
+
+    karatsuba_into(a, b, out, scratch, cutoff, Parallelism::SERIAL);
+}
+
+fn karatsuba_parts(a: &[Limb], b: &[Limb], cutoff: usize, parallel: Parallelism) -> Vec<Limb> {
+    assert!(cutoff >= 2);
+
+    let mut product = vec![0; a.len() + b.len()];
+    let mut scratch = vec![0; parallel_scratch_len(cmp::max(a.len(), b.len()), parallel)];
+    karatsuba_into(a, b, &mut product, &mut scratch, cutoff, parallel);
+    trim_parts(&mut product);
+    product
+}
+
+// x + y into sum, which has room for one part more than the longer of them
+// The result is trimmed so that the recursion only grows by a part when the sum carries
+fn half_sum<'s>(x: &[Limb], y: &[Limb], sum: &'s mut [Limb]) -> &'s [Limb] {
+    let (long, short) = if x.len() >= y.len() { (x, y) } else { (y, x) };
+    let (low, high) = sum.split_at_mut(long.len());
+    low.copy_from_slice(long);
+    for part in high.iter_mut() {
+        *part = 0;
+    }
+    high[0] = limbs::add(low, short) as Limb;
+
+    let len = limbs::significant_len(sum);
+    &sum[..len]
+}
+
+// out = a * b, where out has exactly a.len() + b.len() parts
+// The halves are subslices of a and b, and the two outer products are written
+// straight into the low and high ends of out. The half sums and the middle
+// product take the front of scratch, and every recursive call shares the rest,
+// so nothing is allocated below the top level
+// A level that forks splits the rest three ways instead, one region for each
+// product, and the levels below it fork in turn for as long as parallel allows
+// scratch has to have parallel_scratch_len of the longer operand
+fn karatsuba_into(a: &[Limb], b: &[Limb], out: &mut [Limb], scratch: &mut [Limb], cutoff: usize, parallel: Parallelism) {
     // c = a_h * b_h
     // d = a_l * b_l
     // e = (a_h + a_l)(b_h + b_l) - c - d
     // ab = c * r^n + e * r^n/2 + d
 
-    assert!(cutoff >= 2);
+    if ptr::eq(a, b) {
+        karatsuba_sqr_into(a, out, scratch, cutoff, parallel);
+        return;
+    }
 
-    let p = a.parts.len();
-    let q = b.parts.len();
+    let p = a.len();
+    let q = b.len();
 
-    if p <= cutoff || q <= cutoff {
-        return bignum_long_mult(a, b);
+    // Splitting three parts doesn't shrink the half sums, so stop there whatever the cutoff
+    if p <= cutoff || q <= cutoff || cmp::max(p, q) <= 3 {
+        long_mult_into(a, b, out);
+        return;
     }
 
-    let m = divide_round_up(cmp::max(p, q), 2);
+    let m = cmp::max(p, q).div_ceil(2);
+
+    // When the shorter side fits in one half, its high half would be empty
+    if cmp::min(p, q) <= m {
+        if p >= q {
+            karatsuba_blocks_into(a, b, out, scratch, cutoff, parallel);
+        } else {
+            karatsuba_blocks_into(b, a, out, scratch, cutoff, parallel);
+        }
+        return;
+    }
 
-    let (a_l, a_h) = a.parts.split_at(cmp::min(m, p));
-    let (b_l, b_h) = b.parts.split_at(cmp::min(m, q));
+    let (a_l, a_h) = a.split_at(m);
+    let (b_l, b_h) = b.split_at(m);
 
-    let mut c;
-    let d;
-    let mut e;
+    let (sums, scratch) = scratch.split_at_mut(2 * (m + 1));
+    let (e, scratch) = scratch.split_at_mut(2 * (m + 1));
+    let (a_sum, b_sum) = sums.split_at_mut(m + 1);
+    let a_sum = half_sum(a_h, a_l, a_sum);
+    let b_sum = half_sum(b_h, b_l, b_sum);
+    let e = &mut e[..a_sum.len() + b_sum.len()];
 
     {
-        let a_h_b = Bignum { sign: Nonnegative, parts: a_h.to_vec() };
-        let a_l_b = Bignum { sign: Nonnegative, parts: a_l.to_vec() };
-        let b_h_b = Bignum { sign: Nonnegative, parts: b_h.to_vec() };
-        let b_l_b = Bignum { sign: Nonnegative, parts: b_l.to_vec() };
-        c = karatsuba_rec(&a_h_b, &b_h_b, cutoff);
-        d = karatsuba_rec(&a_l_b, &b_l_b, cutoff);
-        e = bignum_sub(&bignum_sub(
-            &karatsuba_rec(&bignum_add(&a_h_b, &a_l_b), &bignum_add(&b_h_b, &b_l_b), cutoff),
-            &c), &d);
-
-        // Falling out of this block drops the intermediate results
+        // All four halves are nonempty, so d and c fill out between them
+        let (d, c) = out.split_at_mut(2 * m);
+
+        if parallel.forks(cmp::min(p, q)) {
+            let below = parallel.below();
+            let region = parallel_scratch_len(m + 1, below);
+            let (c_scratch, scratch) = scratch.split_at_mut(region);
+            let (d_scratch, e_scratch) = scratch.split_at_mut(region);
+            join(|| karatsuba_into(a_h, b_h, c, c_scratch, cutoff, below),
+                 || join(|| karatsuba_into(a_l, b_l, d, d_scratch, cutoff, below),
+                         || karatsuba_into(a_sum, b_sum, e, e_scratch, cutoff, below)));
+        } else {
+            karatsuba_into(a_h, b_h, c, scratch, cutoff, Parallelism::SERIAL);
+            karatsuba_into(a_l, b_l, d, scratch, cutoff, Parallelism::SERIAL);
+            karatsuba_into(a_sum, b_sum, e, scratch, cutoff, Parallelism::SERIAL);
+        }
+
+        // The halves may have leading zero parts, so c and d can be longer than e
+        limbs::sub(e, &c[..limbs::significant_len(c)]);
+        limbs::sub(e, &d[..limbs::significant_len(d)]);
     }
 
-    shift_left(&mut c, m * 2);
-    shift_left(&mut e, m);
-    let mut result = bignum_add(&c, &bignum_add(&e, &d));
-    result.normalize();
-    result
+    // The whole product fits in out, so e < r^(p + q - m) and this never carries out
+    let e_len = limbs::significant_len(e);
+    limbs::add(&mut out[m..], &e[..e_len]);
 }
 
-// We don't combine the two functions for perf reaons (... maybe)
-fn karatsuba_rec_parallel(a: &Bignum, b: &Bignum, cutoff: usize) -> Bignum {
-    // c = a_h * b_h
-    // d = a_l * b_l
-    // e = (a_h + a_l)(b_h + b_l) - c - d
-    // ab = c * r^n + e * r^n/2 + d
+// out = a * b for b no longer than a's half, rounded up, where out has exactly a.len() + b.len() parts
+// Splitting a in half here would leave b's high half empty, and each of the three
+// half products would still have the whole of b, so the same lopsidedness
+// carries down the recursion. Instead a is taken a block of b.len() parts at a
+// time, in the manner of Toom-2.5 and its relatives, and each block times b is
+// a balanced product, added in where the one before it left off
+// The block products take the front of scratch, which leaves at least
+// parallel_scratch_len(b.len(), parallel) for the rest
+fn karatsuba_blocks_into(a: &[Limb], b: &[Limb], out: &mut [Limb], scratch: &mut [Limb], cutoff: usize, parallel: Parallelism) {
+    let n = b.len();
+    let (first, rest) = a.split_at(n);
 
-    assert!(cutoff >= 2);
+    // The first block goes straight into out, with the rest of out cleared for adding into
+    {
+        let (low, high) = out.split_at_mut(2 * n);
+        karatsuba_into(first, b, low, scratch, cutoff, parallel);
+        for part in high.iter_mut() {
+            *part = 0;
+        }
+    }
 
-    let p = a.parts.len();
-    let q = b.parts.len();
+    let (product, scratch) = scratch.split_at_mut(2 * n);
+    for (i, block) in rest.chunks(n).enumerate() {
+        let product = &mut product[..block.len() + n];
+        karatsuba_into(block, b, product, scratch, cutoff, parallel);
 
-    if p <= cutoff || q <= cutoff {
-        return bignum_long_mult(a, b);
+        // The sum so far fits in the low (i + 2)n parts, so this never carries out of out
+        let len = limbs::significant_len(product);
+        limbs::add(&mut out[(i + 1) * n..], &product[..len]);
     }
+}
 
-    let m = divide_round_up(cmp::max(p, q), 2);
+// out = a * a, where out has exactly twice as many parts as a
+// The same split as karatsuba_into, with both operands the same, so there is
+// only one half sum and all three products are squares. They fit in the same
+// scratch space as a full multiplication
+fn karatsuba_sqr_into(a: &[Limb], out: &mut [Limb], scratch: &mut [Limb], cutoff: usize, parallel: Parallelism) {
+    // c = a_h^2
+    // d = a_l^2
+    // e = (a_h + a_l)^2 - c - d
+    // a^2 = c * r^n + e * r^n/2 + d
 
-    let (a_l, a_h) = a.parts.split_at(cmp::min(m, p));
-    let (b_l, b_h) = b.parts.split_at(cmp::min(m, q));
+    let n = a.len();
 
-    let mut c;
-    let d;
-    let mut e;
+    if n <= cutoff || n <= 3 {
+        sqr_into(a, out);
+        return;
+    }
+
+    let m = n.div_ceil(2);
+    let (a_l, a_h) = a.split_at(m);
+
+    let (sum, scratch) = scratch.split_at_mut(m + 1);
+    let (e, scratch) = scratch.split_at_mut(2 * (m + 1));
+    let sum = half_sum(a_h, a_l, sum);
+    let e = &mut e[..2 * sum.len()];
 
     {
-        let a_h_b = Bignum { sign: Nonnegative, parts: a_h.to_vec() };
-        let a_l_b = Bignum { sign: Nonnegative, parts: a_l.to_vec() };
-        let b_h_b = Bignum { sign: Nonnegative, parts: b_h.to_vec() };
-        let b_l_b = Bignum { sign: Nonnegative, parts: b_l.to_vec() };
-        
-        let (r_1, (r_2, r_3)) =
-            rayon::join(|| karatsuba_rec(&a_h_b, &b_h_b, cutoff),
-                        || rayon:: join( || karatsuba_rec(&a_l_b, &b_l_b, cutoff),
-                                            || karatsuba_rec(&bignum_add(&a_h_b, &a_l_b), &bignum_add(&b_h_b, &b_l_b), cutoff)));
-
-        c = r_1;
-        d = r_2;
-        e = bignum_sub(&bignum_sub(&r_3, &c), &d);
-
-        // Falling out of this block drops the intermediate results
+        // Both halves are nonempty, so d and c fill out between them
+        let (d, c) = out.split_at_mut(2 * m);
+
+        if parallel.forks(n) {
+            let below = parallel.below();
+            let region = parallel_scratch_len(m + 1, below);
+            let (c_scratch, scratch) = scratch.split_at_mut(region);
+            let (d_scratch, e_scratch) = scratch.split_at_mut(region);
+            join(|| karatsuba_sqr_into(a_h, c, c_scratch, cutoff, below),
+                 || join(|| karatsuba_sqr_into(a_l, d, d_scratch, cutoff, below),
+                         || karatsuba_sqr_into(sum, e, e_scratch, cutoff, below)));
+        } else {
+            karatsuba_sqr_into(a_h, c, scratch, cutoff, Parallelism::SERIAL);
+            karatsuba_sqr_into(a_l, d, scratch, cutoff, Parallelism::SERIAL);
+            karatsuba_sqr_into(sum, e, scratch, cutoff, Parallelism::SERIAL);
+        }
+
+        limbs::sub(e, &c[..limbs::significant_len(c)]);
+        limbs::sub(e, &d[..limbs::significant_len(d)]);
     }
 
-    shift_left(&mut c, m * 2);
-    shift_left(&mut e, m);
-    let mut result = bignum_add(&c, &bignum_add(&e, &d));
-    result.normalize();
-    result
+    let e_len = limbs::significant_len(e);
+    limbs::add(&mut out[m..], &e[..e_len]);
 }
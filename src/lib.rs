@@ -1,3 +1,17 @@
 pub mod types;
+pub mod biguint;
+pub mod limbs;
+pub mod parallel;
 pub mod basic_ops;
 pub mod karatsuba;
+pub mod toom_cook;
+pub mod ntt;
+pub mod mul;
+pub mod burnikel_ziegler;
+pub mod newton;
+pub mod decimal;
+mod convert;
+mod float;
+mod helpers;
+mod ops;
+mod radix;
@@ -0,0 +1,231 @@
+use types::*;
+use std::cmp::Ordering;
+
+// Slice-level kernels in the style of GMP's mpn layer
+// Everything works in place on the first argument and hands back whatever
+// falls off the top: a carry or borrow flag for addition and subtraction,
+// and a whole limb for multiplication and shifts
+// Slices are least significant limb first and may have leading zero limbs
+
+// a + b + carry, with the carry out
+fn add_with_carry(a: Limb, b: Limb, carry: bool) -> (Limb, bool) {
+    let (sum, overflow_1) = a.overflowing_add(b);
+    let (sum, overflow_2) = sum.overflowing_add(carry as Limb);
+    (sum, overflow_1 || overflow_2)
+}
+
+// a - b - borrow, with the borrow out
+fn sub_with_borrow(a: Limb, b: Limb, borrow: bool) -> (Limb, bool) {
+    let (difference, overflow_1) = a.overflowing_sub(b);
+    let (difference, overflow_2) = difference.overflowing_sub(borrow as Limb);
+    (difference, overflow_1 || overflow_2)
+}
+
+/// a += b for slices of the same length, returning the carry out
+pub fn add_n(a: &mut [Limb], b: &[Limb]) -> bool {
+    assert_eq!(a.len(), b.len());
+
+    let mut carry = false;
+    for (a_digit, b_digit) in a.iter_mut().zip(b) {
+        let (sum, carry_out) = add_with_carry(*a_digit, *b_digit, carry);
+        *a_digit = sum;
+        carry = carry_out;
+    }
+    carry
+}
+
+/// a -= b for slices of the same length, returning the borrow out
+pub fn sub_n(a: &mut [Limb], b: &[Limb]) -> bool {
+    assert_eq!(a.len(), b.len());
+
+    let mut borrow = false;
+    for (a_digit, b_digit) in a.iter_mut().zip(b) {
+        let (difference, borrow_out) = sub_with_borrow(*a_digit, *b_digit, borrow);
+        *a_digit = difference;
+        borrow = borrow_out;
+    }
+    borrow
+}
+
+/// a += b for a single limb b, returning the carry out
+pub fn add_1(a: &mut [Limb], b: Limb) -> bool {
+    let mut carry = b;
+    for a_digit in a.iter_mut() {
+        if carry == 0 {
+            return false;
+        }
+        let (sum, overflow) = a_digit.overflowing_add(carry);
+        *a_digit = sum;
+        carry = overflow as Limb;
+    }
+    carry != 0
+}
+
+/// a -= b for a single limb b, returning the borrow out
+pub fn sub_1(a: &mut [Limb], b: Limb) -> bool {
+    let mut borrow = b;
+    for a_digit in a.iter_mut() {
+        if borrow == 0 {
+            return false;
+        }
+        let (difference, overflow) = a_digit.overflowing_sub(borrow);
+        *a_digit = difference;
+        borrow = overflow as Limb;
+    }
+    borrow != 0
+}
+
+/// a += b where a is at least as long as b, returning the carry out
+pub fn add(a: &mut [Limb], b: &[Limb]) -> bool {
+    let (low, high) = a.split_at_mut(b.len());
+    let carry = add_n(low, b);
+    carry && add_1(high, 1)
+}
+
+/// a -= b where a is at least as long as b, returning the borrow out
+pub fn sub(a: &mut [Limb], b: &[Limb]) -> bool {
+    let (low, high) = a.split_at_mut(b.len());
+    let borrow = sub_n(low, b);
+    borrow && sub_1(high, 1)
+}
+
+/// a *= b for a single limb b, returning the limb carried out of the top
+pub fn mul_1(a: &mut [Limb], b: Limb) -> Limb {
+    let mut carry: DoubleLimb = 0;
+    for a_digit in a.iter_mut() {
+        let product = *a_digit as DoubleLimb * b as DoubleLimb + carry;
+        *a_digit = product as Limb;
+        carry = product >> LIMB_BITS;
+    }
+    carry as Limb
+}
+
+/// r += a * b for a single limb b and slices of the same length,
+/// returning the limb carried out of the top
+pub fn addmul_1(r: &mut [Limb], a: &[Limb], b: Limb) -> Limb {
+    assert_eq!(r.len(), a.len());
+
+    // (BASE - 1) + (BASE - 1)^2 + (BASE - 1) still fits in a DoubleLimb
+    let mut carry: DoubleLimb = 0;
+    for (r_digit, a_digit) in r.iter_mut().zip(a) {
+        let result = *r_digit as DoubleLimb + *a_digit as DoubleLimb * b as DoubleLimb + carry;
+        *r_digit = result as Limb;
+        carry = result >> LIMB_BITS;
+    }
+    carry as Limb
+}
+
+/// r -= a * b for a single limb b and slices of the same length,
+/// returning the limb borrowed from above the top
+pub fn submul_1(r: &mut [Limb], a: &[Limb], b: Limb) -> Limb {
+    assert_eq!(r.len(), a.len());
+
+    // The borrow stays below BASE, since a * b + borrow < BASE^2
+    let mut borrow: DoubleLimb = 0;
+    for (r_digit, a_digit) in r.iter_mut().zip(a) {
+        let product = *a_digit as DoubleLimb * b as DoubleLimb + borrow;
+        let (difference, overflow) = r_digit.overflowing_sub(product as Limb);
+        *r_digit = difference;
+        borrow = (product >> LIMB_BITS) + overflow as DoubleLimb;
+    }
+    borrow as Limb
+}
+
+/// a <<= bits for bits < LIMB_BITS, returning the bits shifted out of the top
+/// in the low end of a limb
+pub fn lshift(a: &mut [Limb], bits: u32) -> Limb {
+    assert!(bits < LIMB_BITS);
+    if bits == 0 {
+        return 0;
+    }
+
+    let mut carry = 0;
+    for a_digit in a.iter_mut() {
+        let next_carry = *a_digit >> (LIMB_BITS - bits);
+        *a_digit = (*a_digit << bits) | carry;
+        carry = next_carry;
+    }
+    carry
+}
+
+/// a >>= bits for bits < LIMB_BITS, returning the bits shifted out of the bottom
+/// in the high end of a limb
+pub fn rshift(a: &mut [Limb], bits: u32) -> Limb {
+    assert!(bits < LIMB_BITS);
+    if bits == 0 {
+        return 0;
+    }
+
+    let mut carry = 0;
+    for a_digit in a.iter_mut().rev() {
+        let next_carry = *a_digit << (LIMB_BITS - bits);
+        *a_digit = (*a_digit >> bits) | carry;
+        carry = next_carry;
+    }
+    carry
+}
+
+/// a /= b for a single nonzero limb b, returning the remainder
+pub fn divrem_1(a: &mut [Limb], b: Limb) -> Limb {
+    assert!(b != 0, "attempt to divide by zero");
+
+    let mut rem: DoubleLimb = 0;
+    for a_digit in a.iter_mut().rev() {
+        let cur = (rem << LIMB_BITS) | *a_digit as DoubleLimb;
+        *a_digit = (cur / b as DoubleLimb) as Limb;
+        rem = cur % b as DoubleLimb;
+    }
+    rem as Limb
+}
+
+/// a /= b for a single nonzero limb b that divides a exactly
+/// Multiplying by the inverse of b's odd part modulo BASE is much cheaper than
+/// dividing, but the result is meaningless if the division is not exact
+pub fn divexact_1(a: &mut [Limb], b: Limb) {
+    assert!(b != 0, "attempt to divide by zero");
+
+    let shift = b.trailing_zeros();
+    let b = b >> shift;
+    let low_bits = rshift(a, shift);
+    debug_assert!(low_bits == 0, "inexact division");
+
+    // Newton's iteration doubles the correct low bits each time, starting from
+    // the three that b gets right as its own inverse
+    let mut inverse = b;
+    for _ in 0..4 {
+        inverse = inverse.wrapping_mul((2 as Limb).wrapping_sub(b.wrapping_mul(inverse)));
+    }
+
+    let mut borrow = 0;
+    for a_digit in a.iter_mut() {
+        let (difference, borrowed) = a_digit.overflowing_sub(borrow);
+        let quotient = difference.wrapping_mul(inverse);
+        *a_digit = quotient;
+        borrow = ((quotient as DoubleLimb * b as DoubleLimb) >> LIMB_BITS) as Limb + borrowed as Limb;
+    }
+    debug_assert!(borrow == 0, "inexact division");
+}
+
+/// The length of a without its leading zero limbs
+pub fn significant_len(a: &[Limb]) -> usize {
+    a.iter().rposition(|p| *p != 0).map_or(0, |i| i + 1)
+}
+
+/// Compares the values of a and b, which need not be the same length
+pub fn cmp(a: &[Limb], b: &[Limb]) -> Ordering {
+    // Leading zeroes don't count towards the length
+    let p = significant_len(a);
+    let q = significant_len(b);
+
+    if p != q {
+        return p.cmp(&q);
+    }
+
+    // Same number of digits, so the most significant difference decides
+    for (a_digit, b_digit) in a[..p].iter().zip(&b[..q]).rev() {
+        if a_digit != b_digit {
+            return a_digit.cmp(b_digit);
+        }
+    }
+    Ordering::Equal
+}
@@ -0,0 +1,218 @@
+use types::*;
+use biguint::*;
+use basic_ops::*;
+use karatsuba::*;
+use toom_cook::*;
+use ntt::*;
+use parallel::*;
+use std::cmp;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::RwLock;
+
+// Picks a multiplication algorithm from the length of the shorter operand
+// Schoolbook multiplication has no overhead to speak of, so it wins on small
+// operands. Karatsuba, Toom-3 and Toom-4 each trade more bookkeeping for fewer
+// and smaller subproducts, and the NTT's O(n log n) eventually beats them all
+// Karatsuba and Toom-Cook take a much longer operand a block of the shorter
+// one's length at a time, so it is the shorter length that decides
+// The default thresholds are where each overtook the one before it when
+// measured on random operands, with benches/nines.rs as a rough guide
+
+/// Thresholds, in parts of the shorter operand, above which each multiplication
+/// algorithm takes over from the one before it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MulConfig {
+    /// Schoolbook multiplication up to here, and Karatsuba multiplication's own cutoff
+    pub karatsuba_threshold: usize,
+    /// Karatsuba multiplication up to here, and Toom-3's own cutoff
+    pub toom3_threshold: usize,
+    /// Toom-3 up to here, and Toom-4's own cutoff
+    pub toom4_threshold: usize,
+    /// Toom-4 up to here, and the NTT above it, as far as the NTT can go
+    pub ntt_threshold: usize,
+    /// Whether the chosen algorithm splits its work across threads
+    pub parallel: bool,
+    /// How many levels of recursion fork when parallel is set
+    pub parallel_depth: usize,
+    /// The fewest parts the shorter operand can have at a level that forks
+    pub parallel_grain: usize,
+}
+
+impl MulConfig {
+    pub const DEFAULT: MulConfig = MulConfig {
+        karatsuba_threshold: 50,
+        toom3_threshold: 1500,
+        toom4_threshold: 3000,
+        ntt_threshold: 8000,
+        parallel: false,
+        parallel_depth: 3,
+        parallel_grain: 400,
+    };
+
+    /// The config that bignum_mul and the * operator use, which starts out as DEFAULT
+    pub fn global() -> MulConfig {
+        *GLOBAL_CONFIG.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Replaces the config that bignum_mul and the * operator use, on every thread,
+    /// unless it doesn't pass validate, in which case the old one stays
+    pub fn set_global(config: MulConfig) -> Result<(), ParseMulConfigError> {
+        config.validate()?;
+        *GLOBAL_CONFIG.write().unwrap_or_else(|e| e.into_inner()) = config;
+        Ok(())
+    }
+
+    /// Checks that the thresholds leave room for the cutoffs Karatsuba
+    /// multiplication and Toom-Cook assert on, each above the one before it
+    pub fn validate(&self) -> Result<(), ParseMulConfigError> {
+        let thresholds = [
+            ("karatsuba_threshold", self.karatsuba_threshold, 2),
+            ("toom3_threshold", self.toom3_threshold, 4),
+            ("toom4_threshold", self.toom4_threshold, 4),
+            ("ntt_threshold", self.ntt_threshold, 0),
+        ];
+        let mut previous: usize = 0;
+        for &(key, value, cutoff) in &thresholds {
+            let minimum = cmp::max(cutoff, previous.saturating_add(1));
+            if value < minimum {
+                return Err(ParseMulConfigError::InvalidThreshold { key: key.to_string(), value, minimum });
+            }
+            previous = value;
+        }
+        Ok(())
+    }
+
+    /// How the chosen algorithm forks, which is not at all unless parallel is set
+    pub fn parallelism(&self) -> Parallelism {
+        if self.parallel {
+            Parallelism { depth: self.parallel_depth, grain: self.parallel_grain }
+        } else {
+            Parallelism::SERIAL
+        }
+    }
+}
+
+impl Default for MulConfig {
+    fn default() -> MulConfig {
+        MulConfig::DEFAULT
+    }
+}
+
+// Configs are saved as lines of key = value, with # comments, as written by
+// bignum-tune. Keys that are left out keep their default values, and the
+// thresholds have to increase and leave room for each algorithm's cutoff, so that
+// something like
+//
+//     MulConfig::set_global(include_str!("mul.cfg").parse().unwrap()).unwrap()
+//
+// can pick up a tuned config at build time, or the same from a file at runtime
+impl fmt::Display for MulConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "karatsuba_threshold = {}", self.karatsuba_threshold)?;
+        writeln!(f, "toom3_threshold = {}", self.toom3_threshold)?;
+        writeln!(f, "toom4_threshold = {}", self.toom4_threshold)?;
+        writeln!(f, "ntt_threshold = {}", self.ntt_threshold)?;
+        writeln!(f, "parallel = {}", self.parallel)?;
+        writeln!(f, "parallel_depth = {}", self.parallel_depth)?;
+        writeln!(f, "parallel_grain = {}", self.parallel_grain)
+    }
+}
+
+impl FromStr for MulConfig {
+    type Err = ParseMulConfigError;
+
+    fn from_str(s: &str) -> Result<MulConfig, ParseMulConfigError> {
+        let mut config = MulConfig::DEFAULT;
+
+        for (i, line) in s.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (key, value) = match line.split_once('=') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => return Err(ParseMulConfigError::InvalidLine { line: line_number }),
+            };
+            let invalid_value = || ParseMulConfigError::InvalidValue { line: line_number, value: value.to_string() };
+            let number = match key {
+                "karatsuba_threshold" => &mut config.karatsuba_threshold,
+                "toom3_threshold" => &mut config.toom3_threshold,
+                "toom4_threshold" => &mut config.toom4_threshold,
+                "ntt_threshold" => &mut config.ntt_threshold,
+                "parallel_depth" => &mut config.parallel_depth,
+                "parallel_grain" => &mut config.parallel_grain,
+                "parallel" => {
+                    config.parallel = value.parse().map_err(|_| invalid_value())?;
+                    continue;
+                }
+                _ => return Err(ParseMulConfigError::UnknownKey { line: line_number, key: key.to_string() }),
+            };
+            *number = value.parse().map_err(|_| invalid_value())?;
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+static GLOBAL_CONFIG: RwLock<MulConfig> = RwLock::new(MulConfig::DEFAULT);
+
+/// a * b by whichever algorithm suits the operand sizes under the global config
+pub fn bignum_mul<'a, 'b, A, B>(a: A, b: B) -> Bignum
+    where A: Into<BignumRef<'a>>, B: Into<BignumRef<'b>> {
+    bignum_mul_with(a, b, &MulConfig::global())
+}
+
+/// a * b by whichever algorithm suits the operand sizes under the given config
+/// Panics if the config doesn't pass MulConfig::validate
+pub fn bignum_mul_with<'a, 'b, A, B>(a: A, b: B, config: &MulConfig) -> Bignum
+    where A: Into<BignumRef<'a>>, B: Into<BignumRef<'b>> {
+    if let Err(e) = config.validate() {
+        panic!("invalid MulConfig: {}", e);
+    }
+
+    let (a, b) = (a.into(), b.into());
+    let shorter = cmp::min(a.parts.len(), b.parts.len());
+    let longer = cmp::max(a.parts.len(), b.parts.len());
+    let parallel = config.parallelism();
+
+    if shorter <= config.karatsuba_threshold {
+        bignum_long_mult(a, b)
+    } else if shorter <= config.toom3_threshold {
+        bignum_karatsuba_mult(a, b, config.karatsuba_threshold, parallel)
+    } else if shorter <= config.toom4_threshold {
+        bignum_toom3_mult(a, b, config.toom3_threshold, config.karatsuba_threshold, parallel)
+    } else if shorter <= config.ntt_threshold || !ntt_fits(shorter, longer) {
+        bignum_toom_mult(a, b, config.toom4_threshold, config.toom3_threshold, config.karatsuba_threshold, parallel)
+    } else {
+        bignum_ntt_mult(a, b, parallel)
+    }
+}
+
+/// bignum_mul_with, forking onto the given pool's threads rather than rayon's global pool
+#[cfg(feature = "parallel")]
+pub fn bignum_mul_in<'a, 'b, A, B>(pool: &ThreadPool, a: A, b: B, config: &MulConfig) -> Bignum
+    where A: Into<BignumRef<'a>>, B: Into<BignumRef<'b>> {
+    // Checked here too, so that a bad config panics on the caller's thread
+    if let Err(e) = config.validate() {
+        panic!("invalid MulConfig: {}", e);
+    }
+
+    let (a, b) = (a.into(), b.into());
+    pool.install(|| bignum_mul_with(a, b, config))
+}
+
+pub fn biguint_mul(a: &BigUint, b: &BigUint) -> BigUint {
+    biguint_mul_with(a, b, &MulConfig::global())
+}
+
+pub fn biguint_mul_with(a: &BigUint, b: &BigUint, config: &MulConfig) -> BigUint {
+    bignum_mul_with(a.view(), b.view(), config).into_sign_magnitude().1
+}
+
+fn ntt_fits(shorter: usize, longer: usize) -> bool {
+    (shorter + longer - 1).next_power_of_two() <= NTT_MAX_LEN && shorter <= NTT_MAX_SHORTER_LEN
+}
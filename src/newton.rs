@@ -0,0 +1,113 @@
+use types::*;
+use types::Sign::*;
+use basic_ops::*;
+use mul::*;
+use helpers::*;
+use std::cmp;
+
+// Division by Newton-Raphson iteration on the reciprocal,
+// see Brent and Zimmermann, "Modern Computer Arithmetic", section 3.4
+
+// Quotients of at most this many parts are computed with schoolbook division
+const NEWTON_CUTOFF: usize = 16;
+
+/// floor(BASE^precision_limbs / b), carrying the sign of b
+pub fn bignum_reciprocal(b: &Bignum, precision_limbs: usize) -> Result<Bignum, DivideByZeroError> {
+    let b_abs = magnitude(b);
+
+    if b_abs.magnitude.parts.is_empty() {
+        return Err(DivideByZeroError);
+    }
+
+    let reciprocal = with_sign(reciprocal_magnitude(&b_abs, precision_limbs), b.sign);
+    reciprocal.check_invariants();
+    Ok(reciprocal)
+}
+
+/// Truncated division like bignum_divrem, using a Newton reciprocal of the divisor
+pub fn bignum_newton_divrem(a: &Bignum, b: &Bignum) -> Result<(Bignum, Bignum), DivideByZeroError> {
+    let a_abs = magnitude(a);
+    let b_abs = magnitude(b);
+
+    if b_abs.magnitude.parts.is_empty() {
+        return Err(DivideByZeroError);
+    }
+
+    let precision = a_abs.magnitude.parts.len();
+    if precision < b_abs.magnitude.parts.len() + NEWTON_CUTOFF {
+        return bignum_divrem(a, b);
+    }
+
+    // a * x / BASE^precision undershoots a / b by less than 2
+    let x = reciprocal_magnitude(&b_abs, precision);
+    let q = high(&mult(&a_abs, &x), precision);
+    let r = &a_abs - mult(&q, &b_abs);
+    let (quotient, remainder) = correct(q, r, &b_abs);
+
+    let quotient_sign = if a.sign == b.sign { Nonnegative } else { Negative };
+    let quotient = with_sign(quotient, quotient_sign);
+    let remainder = with_sign(remainder, a.sign);
+    quotient.check_invariants();
+    remainder.check_invariants();
+    Ok((quotient, remainder))
+}
+
+fn mult(a: &Bignum, b: &Bignum) -> Bignum {
+    bignum_mul(a, b)
+}
+
+fn power_of_base(exponent: usize) -> Bignum {
+    shifted(&Bignum::from_raw(Nonnegative, vec![1]), exponent)
+}
+
+// Given an estimate q of a / b with remainder r = a - q * b,
+// steps q until 0 <= r < b
+fn correct(mut q: Bignum, mut r: Bignum, b: &Bignum) -> (Bignum, Bignum) {
+    let one = Bignum::from_raw(Nonnegative, vec![1]);
+    while is_negative(&r) {
+        q -= &one;
+        r += b;
+    }
+    while r >= *b {
+        q += &one;
+        r -= b;
+    }
+    (q, r)
+}
+
+fn reciprocal_magnitude(b: &Bignum, p: usize) -> Bignum {
+    let n = b.magnitude.parts.len();
+
+    if p < n {
+        // BASE^p is at most b here
+        let (q, _) = bignum_divrem(&power_of_base(p), b).expect("divisor is nonzero");
+        return trimmed(q);
+    }
+
+    // The reciprocal has m or m - 1 parts
+    let m = p - n + 1;
+
+    if m <= NEWTON_CUTOFF {
+        let (q, _) = bignum_divrem(&power_of_base(p), b).expect("divisor is nonzero");
+        return trimmed(q);
+    }
+
+    // Compute the top m - k parts recursively, keeping a couple of guard parts,
+    // then one Newton step doubles the number of correct parts.
+    // The divisor is truncated by s parts, and the precision lowered by the rest
+    let k = (m - 4) / 2;
+    let s = if n > m - k { cmp::min(k, n - 1 - (m - k)) } else { 0 };
+    let t = k - s;
+
+    let y = reciprocal_magnitude(&high(b, s), p - 2 * s - t);
+    let x_0 = shifted(&y, k);
+
+    // x_1 = x_0 + x_0 * (BASE^p - b * x_0) / BASE^p
+    let e = power_of_base(p) - mult(b, &x_0);
+    let correction = with_sign(high(&mult(&x_0, &magnitude(&e)), p), e.sign);
+    let x_1 = x_0 + &correction;
+
+    let r = power_of_base(p) - mult(b, &x_1);
+    let (x, _) = correct(x_1, r, b);
+    x
+}
@@ -0,0 +1,227 @@
+use types::*;
+use types::Sign::*;
+use biguint::*;
+use parallel::*;
+use std::cmp;
+use std::ptr;
+
+// Multiplication by number-theoretic transform
+// https://en.wikipedia.org/wiki/Discrete_Fourier_transform_over_a_ring#Number-theoretic_transform
+// The parts of a and b are the coefficients of two polynomials, and their
+// product's coefficients are the convolution of the parts. That convolution is
+// done three times, modulo three primes below 2^31 with large power of two
+// factors in p - 1, as a pointwise product between forward and inverse
+// transforms. The Chinese remainder theorem then recovers each coefficient
+// exactly, and the carries between them are propagated as in long multiplication
+
+// A prime modulus for the transform, with a generator of its multiplicative group
+trait NttPrime {
+    const MODULUS: u32;
+    const GENERATOR: u32;
+}
+
+struct Prime1;
+struct Prime2;
+struct Prime3;
+
+// 7 * 2^26 + 1
+impl NttPrime for Prime1 {
+    const MODULUS: u32 = 469_762_049;
+    const GENERATOR: u32 = 3;
+}
+
+// 5 * 2^25 + 1
+impl NttPrime for Prime2 {
+    const MODULUS: u32 = 167_772_161;
+    const GENERATOR: u32 = 3;
+}
+
+// 45 * 2^24 + 1
+impl NttPrime for Prime3 {
+    const MODULUS: u32 = 754_974_721;
+    const GENERATOR: u32 = 11;
+}
+
+/// The longest product, in parts, that NTT multiplication can compute
+/// All three primes have roots of unity of this order
+pub const NTT_MAX_LEN: usize = 1 << 24;
+
+/// The most parts the shorter operand of an NTT multiplication can have
+/// Each coefficient of the product sums this many products of two parts,
+/// which has to stay below the product of the three primes to be recovered
+pub const NTT_MAX_SHORTER_LEN: usize = 3_225_600;
+
+/// a * b by number-theoretic transform, which takes O(n log n) time
+/// When parallel forks, the transforms for each of the three primes run at once
+/// Panics if the operands are longer than NTT_MAX_LEN and NTT_MAX_SHORTER_LEN allow
+pub fn bignum_ntt_mult<'a, 'b, A, B, P>(a: A, b: B, parallel: P) -> Bignum
+    where A: Into<BignumRef<'a>>, B: Into<BignumRef<'b>>, P: Into<Parallelism> {
+    let (a, b) = (a.into(), b.into());
+    let sign = if a.sign == b.sign { Nonnegative } else { Negative };
+    let magnitude = BigUint { parts: ntt_parts(a.parts, b.parts, parallel.into()) };
+    let product = Bignum::from_sign_magnitude(sign, magnitude);
+    product.check_invariants();
+    product
+}
+
+pub fn biguint_ntt_mult<P: Into<Parallelism>>(a: &BigUint, b: &BigUint, parallel: P) -> BigUint {
+    let product = BigUint { parts: ntt_parts(&a.parts, &b.parts, parallel.into()) };
+    product.check_invariants();
+    product
+}
+
+fn ntt_parts(a: &[Limb], b: &[Limb], parallel: Parallelism) -> Vec<Limb> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+
+    let len = (a.len() + b.len() - 1).next_power_of_two();
+    assert!(len <= NTT_MAX_LEN && cmp::min(a.len(), b.len()) <= NTT_MAX_SHORTER_LEN,
+            "operands too long for NTT multiplication");
+
+    let (r1, (r2, r3)) = if parallel.forks(cmp::min(a.len(), b.len())) {
+        join(|| convolve::<Prime1>(a, b, len),
+             || join(|| convolve::<Prime2>(a, b, len),
+                     || convolve::<Prime3>(a, b, len)))
+    } else {
+        (convolve::<Prime1>(a, b, len), (convolve::<Prime2>(a, b, len), convolve::<Prime3>(a, b, len)))
+    };
+
+    let mut product = recombine(&r1, &r2, &r3, a.len() + b.len());
+    trim_parts(&mut product);
+    product
+}
+
+fn mul_mod<P: NttPrime>(a: u32, b: u32) -> u32 {
+    (a as u64 * b as u64 % P::MODULUS as u64) as u32
+}
+
+fn add_mod<P: NttPrime>(a: u32, b: u32) -> u32 {
+    let sum = a + b;
+    if sum >= P::MODULUS { sum - P::MODULUS } else { sum }
+}
+
+fn sub_mod<P: NttPrime>(a: u32, b: u32) -> u32 {
+    if a >= b { a - b } else { a + P::MODULUS - b }
+}
+
+fn pow_mod<P: NttPrime>(mut base: u32, mut exponent: u32) -> u32 {
+    let mut result = 1;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_mod::<P>(result, base);
+        }
+        base = mul_mod::<P>(base, base);
+        exponent >>= 1;
+    }
+    result
+}
+
+// The cyclic convolution of a and b modulo P, over len points
+// Squaring transforms its operand just once
+fn convolve<P: NttPrime>(a: &[Limb], b: &[Limb], len: usize) -> Vec<u32> {
+    let reduce = |parts: &[Limb]| {
+        let mut values: Vec<u32> = parts.iter().map(|part| part % P::MODULUS).collect();
+        values.resize(len, 0);
+        transform::<P>(&mut values, false);
+        values
+    };
+
+    let mut values = reduce(a);
+    if ptr::eq(a, b) {
+        for value in values.iter_mut() {
+            *value = mul_mod::<P>(*value, *value);
+        }
+    } else {
+        for (value, other) in values.iter_mut().zip(reduce(b)) {
+            *value = mul_mod::<P>(*value, other);
+        }
+    }
+
+    transform::<P>(&mut values, true);
+    values
+}
+
+// In-place iterative radix-2 transform, whose length must be a power of two
+// https://en.wikipedia.org/wiki/Cooley%E2%80%93Tukey_FFT_algorithm#Data_reordering,_bit_reversal,_and_in-place_algorithms
+fn transform<P: NttPrime>(values: &mut [u32], inverse: bool) {
+    let n = values.len();
+
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j ^= bit;
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+
+    let mut twiddles = Vec::with_capacity(n / 2);
+    let mut len = 2;
+    while len <= n {
+        // A primitive len-th root of unity, or its inverse
+        let mut root = pow_mod::<P>(P::GENERATOR, (P::MODULUS - 1) / len as u32);
+        if inverse {
+            root = pow_mod::<P>(root, P::MODULUS - 2);
+        }
+
+        let half = len / 2;
+        twiddles.clear();
+        let mut twiddle = 1;
+        for _ in 0..half {
+            twiddles.push(twiddle);
+            twiddle = mul_mod::<P>(twiddle, root);
+        }
+
+        for block in values.chunks_mut(len) {
+            let (low, high) = block.split_at_mut(half);
+            for ((x, y), w) in low.iter_mut().zip(high.iter_mut()).zip(&twiddles) {
+                let u = *x;
+                let v = mul_mod::<P>(*y, *w);
+                *x = add_mod::<P>(u, v);
+                *y = sub_mod::<P>(u, v);
+            }
+        }
+        len <<= 1;
+    }
+
+    if inverse {
+        let n_inverse = pow_mod::<P>(n as u32 % P::MODULUS, P::MODULUS - 2);
+        for value in values.iter_mut() {
+            *value = mul_mod::<P>(*value, n_inverse);
+        }
+    }
+}
+
+// Recovers each coefficient from its residues by Garner's algorithm and adds
+// them up, each one part further along than the last
+fn recombine(r1: &[u32], r2: &[u32], r3: &[u32], len: usize) -> Vec<Limb> {
+    let p1 = Prime1::MODULUS as u64;
+    let p1_p2 = p1 * Prime2::MODULUS as u64;
+    let p1_inverse = pow_mod::<Prime2>(Prime1::MODULUS % Prime2::MODULUS, Prime2::MODULUS - 2);
+    let p1_p2_inverse = pow_mod::<Prime3>((p1_p2 % Prime3::MODULUS as u64) as u32, Prime3::MODULUS - 2);
+
+    let mut parts = Vec::with_capacity(len);
+    let mut carry: u128 = 0;
+    for i in 0..len - 1 {
+        // x = x1 + p1 * x2 + p1 * p2 * x3, with each xk below pk
+        let x1 = r1[i];
+        let x2 = mul_mod::<Prime2>(sub_mod::<Prime2>(r2[i], x1 % Prime2::MODULUS), p1_inverse);
+        let x12 = x1 as u64 + p1 * x2 as u64;
+        let x3 = mul_mod::<Prime3>(sub_mod::<Prime3>(r3[i], (x12 % Prime3::MODULUS as u64) as u32), p1_p2_inverse);
+        let coefficient = x12 as u128 + p1_p2 as u128 * x3 as u128;
+
+        carry += coefficient;
+        parts.push(carry as Limb);
+        carry >>= LIMB_BITS;
+    }
+
+    // The product has len parts, so whatever carries out of the last coefficient fits in one
+    debug_assert!(carry >> LIMB_BITS == 0);
+    parts.push(carry as Limb);
+    parts
+}
@@ -0,0 +1,294 @@
+use types::*;
+use types::Sign::*;
+use basic_ops::*;
+use biguint::*;
+use mul::*;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Sub, SubAssign};
+
+// Operator overloads for Bignum and BigUint
+// Addition and subtraction work in place on whichever operand is owned,
+// the other operators allocate their result and so just borrow both sides
+// Like the primitive unsigned types, BigUint subtraction panics on underflow
+
+impl AddAssign<&Bignum> for Bignum {
+    fn add_assign(&mut self, other: &Bignum) {
+        bignum_add_assign(self, other);
+    }
+}
+
+impl AddAssign<Bignum> for Bignum {
+    fn add_assign(&mut self, other: Bignum) {
+        bignum_add_assign(self, &other);
+    }
+}
+
+impl SubAssign<&Bignum> for Bignum {
+    fn sub_assign(&mut self, other: &Bignum) {
+        bignum_sub_assign(self, other);
+    }
+}
+
+impl SubAssign<Bignum> for Bignum {
+    fn sub_assign(&mut self, other: Bignum) {
+        bignum_sub_assign(self, &other);
+    }
+}
+
+impl Add<&Bignum> for Bignum {
+    type Output = Bignum;
+
+    fn add(mut self, other: &Bignum) -> Bignum {
+        self += other;
+        self
+    }
+}
+
+impl Add<Bignum> for Bignum {
+    type Output = Bignum;
+
+    fn add(mut self, other: Bignum) -> Bignum {
+        self += &other;
+        self
+    }
+}
+
+impl Add<Bignum> for &Bignum {
+    type Output = Bignum;
+
+    fn add(self, mut other: Bignum) -> Bignum {
+        other += self;
+        other
+    }
+}
+
+impl Add<&Bignum> for &Bignum {
+    type Output = Bignum;
+
+    fn add(self, other: &Bignum) -> Bignum {
+        bignum_add(self, other)
+    }
+}
+
+impl Sub<&Bignum> for Bignum {
+    type Output = Bignum;
+
+    fn sub(mut self, other: &Bignum) -> Bignum {
+        self -= other;
+        self
+    }
+}
+
+impl Sub<Bignum> for Bignum {
+    type Output = Bignum;
+
+    fn sub(mut self, other: Bignum) -> Bignum {
+        self -= &other;
+        self
+    }
+}
+
+impl Sub<Bignum> for &Bignum {
+    type Output = Bignum;
+
+    fn sub(self, mut other: Bignum) -> Bignum {
+        // a - b = -(b - a)
+        other -= self;
+        -other
+    }
+}
+
+impl Sub<&Bignum> for &Bignum {
+    type Output = Bignum;
+
+    fn sub(self, other: &Bignum) -> Bignum {
+        bignum_sub(self, other)
+    }
+}
+
+impl Neg for Bignum {
+    type Output = Bignum;
+
+    fn neg(mut self) -> Bignum {
+        if !self.magnitude.parts.is_empty() {
+            self.sign = match self.sign {
+                Negative => Nonnegative,
+                Nonnegative => Negative,
+            };
+        }
+        self.check_invariants();
+        self
+    }
+}
+
+impl Neg for &Bignum {
+    type Output = Bignum;
+
+    fn neg(self) -> Bignum {
+        -self.clone()
+    }
+}
+
+impl AddAssign<&BigUint> for BigUint {
+    fn add_assign(&mut self, other: &BigUint) {
+        add_assign_parts(&mut self.parts, &other.parts);
+    }
+}
+
+impl AddAssign<BigUint> for BigUint {
+    fn add_assign(&mut self, other: BigUint) {
+        *self += &other;
+    }
+}
+
+impl SubAssign<&BigUint> for BigUint {
+    fn sub_assign(&mut self, other: &BigUint) {
+        assert!(*self >= *other, "attempt to subtract with overflow");
+        sub_assign_parts(&mut self.parts, &other.parts);
+    }
+}
+
+impl SubAssign<BigUint> for BigUint {
+    fn sub_assign(&mut self, other: BigUint) {
+        *self -= &other;
+    }
+}
+
+impl Add<&BigUint> for BigUint {
+    type Output = BigUint;
+
+    fn add(mut self, other: &BigUint) -> BigUint {
+        self += other;
+        self
+    }
+}
+
+impl Add<BigUint> for BigUint {
+    type Output = BigUint;
+
+    fn add(mut self, other: BigUint) -> BigUint {
+        self += &other;
+        self
+    }
+}
+
+impl Add<BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, mut other: BigUint) -> BigUint {
+        other += self;
+        other
+    }
+}
+
+impl Add<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: &BigUint) -> BigUint {
+        biguint_add(self, other)
+    }
+}
+
+impl Sub<&BigUint> for BigUint {
+    type Output = BigUint;
+
+    fn sub(mut self, other: &BigUint) -> BigUint {
+        self -= other;
+        self
+    }
+}
+
+impl Sub<BigUint> for BigUint {
+    type Output = BigUint;
+
+    fn sub(mut self, other: BigUint) -> BigUint {
+        self -= &other;
+        self
+    }
+}
+
+impl Sub<BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn sub(self, other: BigUint) -> BigUint {
+        self - &other
+    }
+}
+
+impl Sub<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn sub(self, other: &BigUint) -> BigUint {
+        biguint_sub(self, other).expect("attempt to subtract with overflow")
+    }
+}
+
+fn mult(a: &Bignum, b: &Bignum) -> Bignum {
+    bignum_mul(a, b)
+}
+
+fn mult_unsigned(a: &BigUint, b: &BigUint) -> BigUint {
+    biguint_mul(a, b)
+}
+
+fn div(a: &Bignum, b: &Bignum) -> Bignum {
+    bignum_divrem(a, b).expect("attempt to divide by zero").0
+}
+
+fn rem(a: &Bignum, b: &Bignum) -> Bignum {
+    bignum_divrem(a, b).expect("attempt to calculate the remainder with a divisor of zero").1
+}
+
+// Implements a binary operator for every owned/borrowed combination
+// in terms of a function on two references
+macro_rules! forward_binop {
+    ($t:ty, $imp:ident, $method:ident, $assign_imp:ident, $assign_method:ident, $func:ident) => {
+        impl $imp<&$t> for &$t {
+            type Output = $t;
+
+            fn $method(self, other: &$t) -> $t {
+                $func(self, other)
+            }
+        }
+
+        impl $imp<$t> for &$t {
+            type Output = $t;
+
+            fn $method(self, other: $t) -> $t {
+                $func(self, &other)
+            }
+        }
+
+        impl $imp<&$t> for $t {
+            type Output = $t;
+
+            fn $method(self, other: &$t) -> $t {
+                $func(&self, other)
+            }
+        }
+
+        impl $imp<$t> for $t {
+            type Output = $t;
+
+            fn $method(self, other: $t) -> $t {
+                $func(&self, &other)
+            }
+        }
+
+        impl $assign_imp<&$t> for $t {
+            fn $assign_method(&mut self, other: &$t) {
+                *self = $func(self, other);
+            }
+        }
+
+        impl $assign_imp<$t> for $t {
+            fn $assign_method(&mut self, other: $t) {
+                *self = $func(self, &other);
+            }
+        }
+    }
+}
+
+forward_binop!(Bignum, Mul, mul, MulAssign, mul_assign, mult);
+forward_binop!(Bignum, Div, div, DivAssign, div_assign, div);
+forward_binop!(Bignum, Rem, rem, RemAssign, rem_assign, rem);
+forward_binop!(BigUint, Mul, mul, MulAssign, mul_assign, mult_unsigned);
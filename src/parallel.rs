@@ -0,0 +1,81 @@
+#[cfg(feature = "parallel")]
+extern crate rayon;
+
+// rayon's pool types, so that callers can build a pool of the same version
+#[cfg(feature = "parallel")]
+pub use self::rayon::{Configuration, ThreadPool};
+
+// The recursive multiplications fork with rayon::join, which runs on whichever
+// pool it is called from. Calls made inside ThreadPool::install, or through
+// bignum_mul_in, use that pool's threads and no others; anything else uses
+// rayon's global pool
+// Without the parallel feature there is no rayon, and everything runs serially
+// on the calling thread, whatever Parallelism it is given
+
+/// How far a multiplication splits its work across threads
+/// Each level of recursion forks until depth levels have, as long as the
+/// shorter operand at that level has at least grain parts
+/// The multiplications take anything that converts into one, so true and false
+/// can stand in for TOP_LEVEL and SERIAL
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Parallelism {
+    /// How many levels of recursion fork, where 0 is serial
+    pub depth: usize,
+    /// The fewest parts the shorter operand can have at a level that forks
+    pub grain: usize,
+}
+
+impl Parallelism {
+    pub const SERIAL: Parallelism = Parallelism { depth: 0, grain: 0 };
+
+    /// Forking at the top level only, whatever the size
+    pub const TOP_LEVEL: Parallelism = Parallelism { depth: 1, grain: 0 };
+
+    /// Whether a level whose shorter operand has len parts forks, which is
+    /// never without the parallel feature
+    pub fn forks(&self, len: usize) -> bool {
+        cfg!(feature = "parallel") && self.depth > 0 && len >= self.grain
+    }
+
+    /// What is left for the levels below one that forked
+    pub fn below(&self) -> Parallelism {
+        Parallelism { depth: self.depth.saturating_sub(1), ..*self }
+    }
+}
+
+impl From<bool> for Parallelism {
+    fn from(parallel: bool) -> Parallelism {
+        if parallel { Parallelism::TOP_LEVEL } else { Parallelism::SERIAL }
+    }
+}
+
+// a and b, on two threads if rayon can find a free one
+#[cfg(feature = "parallel")]
+pub(crate) fn join<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+    where A: FnOnce() -> RA + Send, B: FnOnce() -> RB + Send, RA: Send, RB: Send {
+    rayon::join(a, b)
+}
+
+#[cfg(not(feature = "parallel"))]
+pub(crate) fn join<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+    where A: FnOnce() -> RA + Send, B: FnOnce() -> RB + Send, RA: Send, RB: Send {
+    (a(), b())
+}
+
+// f at each of 0..n, in order, forking by halves of the range
+pub(crate) fn join_map<R, F>(n: usize, f: &F) -> Vec<R>
+    where F: Fn(usize) -> R + Sync, R: Send {
+    join_map_range(0, n, f)
+}
+
+fn join_map_range<R, F>(start: usize, end: usize, f: &F) -> Vec<R>
+    where F: Fn(usize) -> R + Sync, R: Send {
+    if end - start <= 1 {
+        return (start..end).map(f).collect();
+    }
+
+    let middle = start + (end - start) / 2;
+    let (mut low, high) = join(|| join_map_range(start, middle, f), || join_map_range(middle, end, f));
+    low.extend(high);
+    low
+}
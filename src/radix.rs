@@ -0,0 +1,258 @@
+use types::*;
+use types::Sign::*;
+use decimal::{parts_from_decimal, parts_to_decimal};
+use std::fmt;
+
+// Conversion to and from strings in any radix from 2 to 36
+// Power of two radixes are packed bit by bit, the others go through
+// the largest power of the radix that fits in a single part
+
+impl Bignum {
+    pub fn from_str_radix(s: &str, radix: u32) -> Result<Bignum, ParseBignumError> {
+        parse_radix(s, radix, false, false)
+    }
+
+    /// Like from_str_radix, but also accepts a 0x, 0o or 0b prefix after the sign
+    /// when it matches a radix of 16, 8 or 2 respectively
+    pub fn from_str_radix_lenient(s: &str, radix: u32) -> Result<Bignum, ParseBignumError> {
+        parse_radix(s, radix, true, false)
+    }
+
+    /// Lowercase digits, with a leading '-' for negative numbers
+    pub fn to_str_radix(&self, radix: u32) -> String {
+        let digits = if radix == 10 {
+            parts_to_decimal(&self.magnitude.parts, false)
+        } else {
+            magnitude_to_str_radix(&self.magnitude.parts, radix)
+        };
+        if self.sign == Negative && digits != "0" {
+            format!("-{}", digits)
+        } else {
+            digits
+        }
+    }
+}
+
+fn check_radix(radix: u32) {
+    assert!((2..=36).contains(&radix), "radix must lie in the range [2, 36], found {}", radix);
+}
+
+fn prefix_for_radix(radix: u32) -> Option<[char; 2]> {
+    match radix {
+        16 => Some(['x', 'X']),
+        8 => Some(['o', 'O']),
+        2 => Some(['b', 'B']),
+        _ => None,
+    }
+}
+
+// Strips a prefix like "0x" when it is followed by at least one more character
+fn strip_radix_prefix(s: &str, radix: u32) -> &str {
+    if let Some(letters) = prefix_for_radix(radix) {
+        if let Some(rest) = s.strip_prefix('0') {
+            if let Some(digits) = rest.strip_prefix(letters[0]).or_else(|| rest.strip_prefix(letters[1])) {
+                if !digits.is_empty() {
+                    return digits;
+                }
+            }
+        }
+    }
+    s
+}
+
+pub(crate) fn parse_radix(input_str: &str, radix: u32, lenient: bool, parallel: bool)
+                          -> Result<Bignum, ParseBignumError> {
+    check_radix(radix);
+
+    if input_str.is_empty() {
+        return Err(ParseBignumError::Empty);
+    }
+
+    let (sign, unsigned) = if let Some(rest) = input_str.strip_prefix('-') {
+        (Negative, rest)
+    } else if let Some(rest) = input_str.strip_prefix('+') {
+        (Nonnegative, rest)
+    } else {
+        (Nonnegative, input_str)
+    };
+
+    if unsigned.is_empty() {
+        return Err(ParseBignumError::SignOnly);
+    }
+
+    let digits_str = if lenient { strip_radix_prefix(unsigned, radix) } else { unsigned };
+    let offset = input_str.len() - digits_str.len();
+
+    let mut digits = Vec::with_capacity(digits_str.len());
+    for (i, c) in digits_str.char_indices() {
+        match c.to_digit(radix) {
+            Some(d) => digits.push(d),
+            None => return Err(ParseBignumError::InvalidDigit { index: offset + i, char: c }),
+        }
+    }
+
+    let parts = if radix.is_power_of_two() {
+        pack_bits(&digits, radix.trailing_zeros())
+    } else if radix == 10 {
+        parts_from_decimal(&digits, parallel)
+    } else {
+        pack_chunks(&digits, radix)
+    };
+
+    let mut result = Bignum::from_raw(sign, parts);
+    result.normalize();
+    result.check_invariants();
+    Ok(result)
+}
+
+// Digits are most significant first, each holding the given number of bits
+fn pack_bits(digits: &[u32], bits: u32) -> Vec<Limb> {
+    let mut parts = Vec::with_capacity(digits.len() * bits as usize / LIMB_BITS as usize + 1);
+    let mut current: DoubleLimb = 0;
+    let mut filled = 0;
+
+    for d in digits.iter().rev() {
+        current |= (*d as DoubleLimb) << filled;
+        filled += bits;
+        if filled >= LIMB_BITS {
+            parts.push(current as Limb);
+            current >>= LIMB_BITS;
+            filled -= LIMB_BITS;
+        }
+    }
+    if filled > 0 {
+        parts.push(current as Limb);
+    }
+    parts
+}
+
+// The largest power of radix that fits in a part, and its exponent
+fn chunk_for_radix(radix: u32) -> (Limb, usize) {
+    let mut chunk: Limb = radix;
+    let mut chunk_len = 1;
+    while let Some(next) = chunk.checked_mul(radix) {
+        chunk = next;
+        chunk_len += 1;
+    }
+    (chunk, chunk_len)
+}
+
+pub(crate) fn pack_chunks(digits: &[u32], radix: u32) -> Vec<Limb> {
+    let (_, chunk_len) = chunk_for_radix(radix);
+    let mut parts: Vec<Limb> = Vec::new();
+
+    // The first chunk takes whatever is left over, so the rest are all full
+    let first_len = match digits.len() % chunk_len {
+        0 => chunk_len,
+        n => n,
+    };
+    let mut start = 0;
+    let mut end = first_len;
+
+    while start < digits.len() {
+        let mut multiplier: DoubleLimb = 1;
+        let mut value: DoubleLimb = 0;
+        for d in &digits[start..end] {
+            value = value * radix as DoubleLimb + *d as DoubleLimb;
+            multiplier *= radix as DoubleLimb;
+        }
+
+        // parts = parts * multiplier + value
+        let mut carry = value;
+        for part in parts.iter_mut() {
+            let result = *part as DoubleLimb * multiplier + carry;
+            *part = result as Limb;
+            carry = result >> LIMB_BITS;
+        }
+        if carry > 0 {
+            parts.push(carry as Limb);
+        }
+
+        start = end;
+        end += chunk_len;
+    }
+
+    parts
+}
+
+// Quadratic in the number of parts, see decimal for the faster radix 10 conversion
+pub(crate) fn magnitude_to_str_radix(parts: &[Limb], radix: u32) -> String {
+    check_radix(radix);
+
+    let mut digits: Vec<u32> = Vec::new(); // Least significant first
+
+    if radix.is_power_of_two() {
+        let bits = radix.trailing_zeros();
+        let mask = (radix - 1) as DoubleLimb;
+        let mut current: DoubleLimb = 0;
+        let mut filled = 0;
+
+        for part in parts {
+            current |= (*part as DoubleLimb) << filled;
+            filled += LIMB_BITS;
+            while filled >= bits {
+                digits.push((current & mask) as u32);
+                current >>= bits;
+                filled -= bits;
+            }
+        }
+        if filled > 0 {
+            digits.push(current as u32);
+        }
+    } else {
+        let (chunk, chunk_len) = chunk_for_radix(radix);
+        let mut quotient = parts.to_vec();
+
+        while !quotient.is_empty() {
+            // Divide by chunk in place, then split the remainder into digits
+            let mut rem: DoubleLimb = 0;
+            for part in quotient.iter_mut().rev() {
+                let cur = (rem << LIMB_BITS) | *part as DoubleLimb;
+                *part = (cur / chunk as DoubleLimb) as Limb;
+                rem = cur % chunk as DoubleLimb;
+            }
+            while quotient.last() == Some(&0) {
+                quotient.pop();
+            }
+
+            for _ in 0..chunk_len {
+                digits.push((rem % radix as DoubleLimb) as u32);
+                rem /= radix as DoubleLimb;
+            }
+        }
+    }
+
+    while digits.len() > 1 && digits.last() == Some(&0) {
+        digits.pop();
+    }
+    if digits.is_empty() {
+        return "0".to_string();
+    }
+
+    digits.iter().rev().map(|d| ::std::char::from_digit(*d, radix).unwrap()).collect()
+}
+
+impl fmt::LowerHex for Bignum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(self.sign == Nonnegative, "0x", &magnitude_to_str_radix(&self.magnitude.parts, 16))
+    }
+}
+
+impl fmt::UpperHex for Bignum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = magnitude_to_str_radix(&self.magnitude.parts, 16).to_uppercase();
+        f.pad_integral(self.sign == Nonnegative, "0x", &digits)
+    }
+}
+
+impl fmt::Octal for Bignum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(self.sign == Nonnegative, "0o", &magnitude_to_str_radix(&self.magnitude.parts, 8))
+    }
+}
+
+impl fmt::Binary for Bignum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(self.sign == Nonnegative, "0b", &magnitude_to_str_radix(&self.magnitude.parts, 2))
+    }
+}
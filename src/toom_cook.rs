@@ -0,0 +1,259 @@
+use types::*;
+use types::Sign::*;
+use biguint::*;
+use basic_ops::*;
+use karatsuba::*;
+use limbs;
+use parallel::*;
+use std::cmp;
+use std::ptr;
+
+// Toom-Cook multiplication
+// https://en.wikipedia.org/wiki/Toom%E2%80%93Cook_multiplication
+// Each operand is split into k pieces of s parts, read as the coefficients of
+// a polynomial in r^s. Both polynomials are evaluated at 2k - 1 points, the
+// values multiplied pointwise, and the product polynomial interpolated back
+// from those products. Toom-3 does five multiplications of a third the size
+// where long multiplication does nine, and Toom-4 does seven of a quarter the
+// size where it does sixteen
+// The interpolation goes through divided differences, whose divisions are
+// all exact for polynomials with integer coefficients
+
+// The evaluation points besides infinity, in the order they are used
+const POINTS: [i64; 6] = [0, 1, -1, 2, -2, 3];
+
+// Where the recursion hands over to the next algorithm down: Toom-4 above
+// toom4, Toom-3 above toom3, and Karatsuba multiplication with karatsuba below
+// that, going by the shorter side
+#[derive(Clone, Copy)]
+struct Cutoffs {
+    toom4: usize,
+    toom3: usize,
+    karatsuba: usize,
+}
+
+/// a * b by Toom-3 multiplication, falling back to Karatsuba multiplication with
+/// karatsuba_cutoff once either side has cutoff parts or fewer
+pub fn bignum_toom3_mult<'a, 'b, A, B, P>(a: A, b: B, cutoff: usize, karatsuba_cutoff: usize, parallel: P) -> Bignum
+    where A: Into<BignumRef<'a>>, B: Into<BignumRef<'b>>, P: Into<Parallelism> {
+    let cutoffs = Cutoffs { toom4: usize::MAX, toom3: cutoff, karatsuba: karatsuba_cutoff };
+    toom_mult(a.into(), b.into(), cutoffs, parallel.into())
+}
+
+/// a * b by Toom-4 multiplication, falling back to Karatsuba multiplication with
+/// karatsuba_cutoff once either side has cutoff parts or fewer
+pub fn bignum_toom4_mult<'a, 'b, A, B, P>(a: A, b: B, cutoff: usize, karatsuba_cutoff: usize, parallel: P) -> Bignum
+    where A: Into<BignumRef<'a>>, B: Into<BignumRef<'b>>, P: Into<Parallelism> {
+    let cutoffs = Cutoffs { toom4: cutoff, toom3: cutoff, karatsuba: karatsuba_cutoff };
+    toom_mult(a.into(), b.into(), cutoffs, parallel.into())
+}
+
+/// a * b by Toom-4 multiplication, falling back to Toom-3 once either side has
+/// toom4_cutoff parts or fewer, and from there to Karatsuba multiplication as
+/// bignum_toom3_mult does
+pub fn bignum_toom_mult<'a, 'b, A, B, P>(a: A, b: B, toom4_cutoff: usize, toom3_cutoff: usize, karatsuba_cutoff: usize,
+                                         parallel: P) -> Bignum
+    where A: Into<BignumRef<'a>>, B: Into<BignumRef<'b>>, P: Into<Parallelism> {
+    let cutoffs = Cutoffs { toom4: toom4_cutoff, toom3: toom3_cutoff, karatsuba: karatsuba_cutoff };
+    toom_mult(a.into(), b.into(), cutoffs, parallel.into())
+}
+
+pub fn biguint_toom3_mult<P>(a: &BigUint, b: &BigUint, cutoff: usize, karatsuba_cutoff: usize, parallel: P) -> BigUint
+    where P: Into<Parallelism> {
+    bignum_toom3_mult(a.view(), b.view(), cutoff, karatsuba_cutoff, parallel).into_sign_magnitude().1
+}
+
+pub fn biguint_toom4_mult<P>(a: &BigUint, b: &BigUint, cutoff: usize, karatsuba_cutoff: usize, parallel: P) -> BigUint
+    where P: Into<Parallelism> {
+    bignum_toom4_mult(a.view(), b.view(), cutoff, karatsuba_cutoff, parallel).into_sign_magnitude().1
+}
+
+pub fn biguint_toom_mult<P>(a: &BigUint, b: &BigUint, toom4_cutoff: usize, toom3_cutoff: usize, karatsuba_cutoff: usize,
+                            parallel: P) -> BigUint
+    where P: Into<Parallelism> {
+    bignum_toom_mult(a.view(), b.view(), toom4_cutoff, toom3_cutoff, karatsuba_cutoff, parallel).into_sign_magnitude().1
+}
+
+fn toom_mult(a: BignumRef, b: BignumRef, cutoffs: Cutoffs, parallel: Parallelism) -> Bignum {
+    // Below this the pieces, plus the growth from evaluation, don't come out shorter than the operands
+    assert!(cutoffs.toom4 >= 4 && cutoffs.toom3 >= 4);
+
+    let sign = if a.sign == b.sign { Nonnegative } else { Negative };
+    let magnitude = toom_rec(a.parts, b.parts, cutoffs, parallel);
+    let product = Bignum::from_sign_magnitude(sign, magnitude.into_sign_magnitude().1);
+    product.check_invariants();
+    product
+}
+
+// |a| * |b|, which is never negative
+// As with Karatsuba multiplication, each level forks for as long as parallel allows
+fn toom_rec(a: &[Limb], b: &[Limb], cutoffs: Cutoffs, parallel: Parallelism) -> Bignum {
+    let p = a.len();
+    let q = b.len();
+
+    let k = if cmp::min(p, q) > cutoffs.toom4 {
+        4
+    } else if cmp::min(p, q) > cutoffs.toom3 {
+        3
+    } else {
+        let a = BignumRef { parts: a, sign: Nonnegative };
+        let b = BignumRef { parts: b, sign: Nonnegative };
+        return bignum_karatsuba_mult(a, b, cutoffs.karatsuba, parallel);
+    };
+
+    // When one side is less than half the length of the other, most of its
+    // pieces would be empty, so the longer side goes a block at a time instead
+    if 2 * p < q {
+        return toom_blocks(b, a, cutoffs, parallel);
+    } else if 2 * q < p {
+        return toom_blocks(a, b, cutoffs, parallel);
+    }
+
+    let s = cmp::max(p, q).div_ceil(k);
+    let points = &POINTS[..2 * k - 2];
+
+    // Squaring evaluates once, and the pointwise products are squares too
+    let a_values = evaluate(a, k, s, points);
+    let b_values = if ptr::eq(a, b) { None } else { Some(evaluate(b, k, s, points)) };
+    let forks = parallel.forks(cmp::min(p, q));
+    let below = if forks { parallel.below() } else { Parallelism::SERIAL };
+    let product_at = |i: usize| {
+        let x = &a_values[i];
+        let y = b_values.as_ref().map_or(x, |values| &values[i]);
+        let product = toom_rec(x.limbs(), y.limbs(), cutoffs, below);
+        if x.sign() == y.sign() { product } else { -product }
+    };
+
+    let products = if forks {
+        join_map(2 * k - 1, &product_at)
+    } else {
+        (0..2 * k - 1).map(product_at).collect()
+    };
+
+    // Evaluate the product polynomial at r^s, summing the positive and negative
+    // coefficients apart so that neither sum can go below zero
+    // Each coefficient is at most 2k - 1 products of pieces, so both sums fit in 2ks + 1 parts
+    let len = 2 * k * s + 1;
+    let mut positive = vec![0; len];
+    let mut negative = vec![0; len];
+    for (i, coefficient) in interpolate(products, points).iter().enumerate() {
+        let sum = if coefficient.sign() == Nonnegative { &mut positive } else { &mut negative };
+        limbs::add(&mut sum[i * s..], coefficient.limbs());
+    }
+
+    let borrow = limbs::sub(&mut positive, &negative);
+    debug_assert!(!borrow, "negative product in Toom-Cook multiplication");
+    trim_parts(&mut positive);
+    Bignum::from_raw(Nonnegative, positive)
+}
+
+// |a| * |b| for b less than half the length of a, as the products of b with
+// each block of b.len() parts of a, which are balanced, added together one
+// block further along each time
+fn toom_blocks(a: &[Limb], b: &[Limb], cutoffs: Cutoffs, parallel: Parallelism) -> Bignum {
+    let n = b.len();
+    let mut sum = vec![0; a.len() + n];
+    for (i, block) in a.chunks(n).enumerate() {
+        let block = &block[..limbs::significant_len(block)];
+        let product = toom_rec(block, b, cutoffs, parallel);
+        limbs::add(&mut sum[i * n..], product.limbs());
+    }
+
+    trim_parts(&mut sum);
+    Bignum::from_raw(Nonnegative, sum)
+}
+
+// The ith of the k pieces of s parts, which is empty past the end of a
+fn piece(a: &[Limb], i: usize, s: usize) -> BignumRef<'_> {
+    let start = cmp::min(i * s, a.len());
+    let end = cmp::min(start + s, a.len());
+    let parts = &a[start..end];
+    BignumRef { parts: &parts[..limbs::significant_len(parts)], sign: Nonnegative }
+}
+
+// The polynomial with a's pieces as coefficients at each of the points, and
+// its top coefficient as the value at infinity
+// The points come in pairs t and -t, which share the sums of the even and odd
+// terms, so each pair costs one evaluation by Horner's rule in t^2
+fn evaluate(a: &[Limb], k: usize, s: usize, points: &[i64]) -> Vec<Bignum> {
+    let mut values = Vec::with_capacity(points.len() + 1);
+    let mut i = 0;
+    while i < points.len() {
+        let t = points[i];
+        let (even, odd) = even_odd_parts(a, k, s, t);
+        if i + 1 < points.len() && points[i + 1] == -t && t != 0 {
+            values.push(&even + &odd);
+            values.push(&even - &odd);
+            i += 2;
+        } else {
+            values.push(&even + &odd);
+            i += 1;
+        }
+    }
+    values.push(piece(a, k - 1, s).to_bignum());
+    values
+}
+
+// The sums of the even and odd terms of the polynomial at t
+fn even_odd_parts(a: &[Limb], k: usize, s: usize, t: i64) -> (Bignum, Bignum) {
+    let mut parts = [Bignum::from(0u8), Bignum::from(0u8)];
+    for i in (0..k).rev() {
+        let sum = &mut parts[i % 2];
+        *sum = bignum_add(&mul_small(sum, t * t), piece(a, i, s));
+    }
+
+    // The odd sum is missing its common factor of t
+    let [even, odd] = parts;
+    (even, mul_small(&odd, t))
+}
+
+// The coefficients of the polynomial of degree points.len() which takes the
+// given values at the points, with the last value being the one at infinity
+fn interpolate(mut values: Vec<Bignum>, points: &[i64]) -> Vec<Bignum> {
+    let d = points.len();
+    let top = values.pop().unwrap();
+
+    // Taking off the top term leaves a polynomial of degree d - 1, fixed by its
+    // values at the d points. Its divided differences are its coefficients in
+    // the Newton basis 1, (x - x_0), (x - x_0)(x - x_1), ...
+    let mut newton: Vec<Bignum> = values.iter().zip(points)
+        .map(|(value, t)| value - &mul_small(&top, t.pow(d as u32)))
+        .collect();
+    for j in 1..d {
+        for i in (j..d).rev() {
+            newton[i] = div_exact(&(&newton[i] - &newton[i - 1]), points[i] - points[i - j]);
+        }
+    }
+
+    // Back to the monomial basis, multiplying out from the innermost bracket
+    let mut coefficients = vec![newton[d - 1].clone()];
+    for i in (0..d - 1).rev() {
+        let mut next = Vec::with_capacity(coefficients.len() + 1);
+        next.push(&newton[i] - &mul_small(&coefficients[0], points[i]));
+        for j in 1..coefficients.len() {
+            next.push(&coefficients[j - 1] - &mul_small(&coefficients[j], points[i]));
+        }
+        next.push(coefficients[coefficients.len() - 1].clone());
+        coefficients = next;
+    }
+
+    coefficients.push(top);
+    coefficients
+}
+
+fn mul_small(a: &Bignum, t: i64) -> Bignum {
+    match t {
+        0 => Bignum::from(0u8),
+        1 => a.clone(),
+        -1 => -a,
+        _ => bignum_long_mult(a, &Bignum::from(t)),
+    }
+}
+
+fn div_exact(a: &Bignum, t: i64) -> Bignum {
+    let mut parts = a.limbs().to_vec();
+    limbs::divexact_1(&mut parts, t.unsigned_abs() as Limb);
+    trim_parts(&mut parts);
+    let quotient = Bignum::from_sign_magnitude(a.sign(), BigUint { parts });
+    if t < 0 { -quotient } else { quotient }
+}
@@ -1,6 +1,12 @@
-use std::cmp;
-
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+use biguint::BigUint;
+use decimal::parts_to_decimal;
+use radix::parse_radix;
+use std::error::Error;
+use std::fmt;
+use std::iter;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Sign {
     Nonnegative = 1,
     Negative = -1,
@@ -8,254 +14,297 @@ pub enum Sign {
 
 use self::Sign::*;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+// A single digit of a Bignum, and a type wide enough for the product of two
+pub type Limb = u32;
+pub type DoubleLimb = u64;
+
+// A sign and a magnitude; the magnitude has no leading zero parts,
+// and zero itself is stored as empty parts with a Nonnegative sign
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Bignum {
-    pub parts: Vec<u32>, // Least significant digit at leftmost index
-    pub sign: Sign,
+    pub(crate) magnitude: BigUint,
+    pub(crate) sign: Sign,
 }
 
-#[derive(Debug)]
-pub struct ParseBignumError;
-
-// Max value of a single part
-// Needs to be less than half the max value of the integer type
-// To allow adding without overflow
-pub const BASE: u32 = 2000000000;
-pub const BASE_STR: &'static str = "2000000000";
-
-fn skip_leading_zeroes(s: &str) -> &str {
-    let mut chars = s.chars();
-    let mut first_nonzero_index = 0;
-
-    loop {
-        let c = chars.next();
-        if let Some('0') = c {
-            first_nonzero_index += 1;
-        } else {
-            break;
+// A borrowed view of a Bignum, or of any slice of limbs with the same invariants
+// Being Copy, it can be negated or split without touching the limbs themselves
+#[derive(Clone, Copy)]
+pub struct BignumRef<'a> {
+    pub(crate) parts: &'a [Limb],
+    pub(crate) sign: Sign,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseBignumError {
+    Empty,
+    // Byte index into the input, counting the sign
+    InvalidDigit { index: usize, char: char },
+    SignOnly,
+}
+
+impl fmt::Display for ParseBignumError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseBignumError::Empty =>
+                write!(f, "cannot parse bignum from empty string"),
+            ParseBignumError::InvalidDigit { index, char } =>
+                write!(f, "invalid digit {:?} at index {}", char, index),
+            ParseBignumError::SignOnly =>
+                write!(f, "cannot parse bignum from a sign with no digits"),
         }
     }
+}
+
+impl Error for ParseBignumError {}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct DivideByZeroError;
 
-    if first_nonzero_index >= s.len() {
-        "0"
-    } else {
-        &s[first_nonzero_index..]
+// Returned when a Bignum does not fit in the primitive integer it is converted to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TryFromBignumError;
+
+impl fmt::Display for TryFromBignumError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "bignum out of range for the target integer type")
     }
 }
 
-fn char_to_digit(c: char) -> u32 {
-    c as u32 - '0' as u32
-}
+impl Error for TryFromBignumError {}
 
-fn digit_to_char(part: u32) -> char {
-    ::std::char::from_u32(part + '0' as u32).unwrap()
+// Every Limb value is a valid part, so only the canonical form can be broken
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvalidLimbError {
+    // Index of the most significant limb, which was zero
+    LeadingZero { index: usize },
+    NegativeZero,
 }
 
-fn string_add<'a, 'b>(left: &'a mut String, right: &'b str) -> &'a String {
-    let l = left.chars().collect::<Vec<char>>();
-    let r = right.chars().collect::<Vec<char>>();
+impl fmt::Display for InvalidLimbError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            InvalidLimbError::LeadingZero { index } =>
+                write!(f, "most significant limb at index {} is zero", index),
+            InvalidLimbError::NegativeZero =>
+                write!(f, "zero cannot be negative"),
+        }
+    }
+}
 
-    let p = l.len();
-    let q = r.len();
+impl Error for InvalidLimbError {}
 
-    let max = cmp::max(p, q);
+// Returned when a subtraction of BigUints would go below zero
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnderflowError;
 
-    let mut temp_str = String::with_capacity(max + 1);
-    let mut carry: u32 = 0;
-    for i in 1..max+1 {
-        let l_digit = if p >= i {
-            char_to_digit(l[p-i])
-        } else {
-            0
-        };
-        let r_digit = if q >= i {
-            char_to_digit(r[q-i])
-        } else {
-            0
-        };
-        let result: u64 = (l_digit + r_digit + carry) as u64;
-        temp_str.push(digit_to_char((result % 10) as u32));
-        carry = (result / 10) as u32;
+impl fmt::Display for UnderflowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "attempt to subtract a larger BigUint from a smaller one")
     }
-    if carry > 0 {
-        temp_str.push(digit_to_char(carry));
+}
+
+impl Error for UnderflowError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FromFloatError {
+    NaN,
+    Infinite,
+}
+
+impl fmt::Display for FromFloatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FromFloatError::NaN =>
+                write!(f, "cannot convert NaN to bignum"),
+            FromFloatError::Infinite =>
+                write!(f, "cannot convert an infinite value to bignum"),
+        }
     }
+}
+
+impl Error for FromFloatError {}
 
-    left.clone_from(&temp_str.chars().rev().collect::<String>());
-    left
+// Returned when a multiplication config file doesn't parse, with 1-based line numbers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseMulConfigError {
+    // A line that is neither blank, a comment, nor key = value
+    InvalidLine { line: usize },
+    UnknownKey { line: usize, key: String },
+    InvalidValue { line: usize, value: String },
+    // A threshold the algorithms can't run with: karatsuba_threshold below 2, a
+    // Toom threshold below 4, or one that isn't above the threshold before it
+    InvalidThreshold { key: String, value: usize, minimum: usize },
 }
 
-fn string_mult<'a, 'b>(left: &'a mut String, right: &'b str) -> &'a String {
-    let l = left.chars().collect::<Vec<char>>();
-    let r = right.chars().collect::<Vec<char>>();
-
-    let p = l.len();
-    let q = r.len();
-
-    let (top, bot) = if p >= q {
-        (l, r)
-    } else {
-        (r, l)
-    };
-
-    let min = cmp::min(p, q);
-    let max = cmp::max(p, q);
-
-    let mut temp_str = String::with_capacity(p + q);
-    temp_str.push('0');
-    let mut carry: u32 = 0;
-    for i in 1..min+1 {
-        // For each digit of the bottom, multiply by the top
-        let mut line_str = String::with_capacity(max + 1);
-        for j in 1..max+1 {
-            let top_digit = if max >= j {
-                char_to_digit(top[max-j])
-            } else {
-                0
-            };
-            let bot_digit = if min >= i {
-                char_to_digit(bot[min-i])
-            } else {
-                0
-            };
-            let result: u64 = (top_digit * bot_digit + carry) as u64;
-            line_str.push(digit_to_char((result % 10) as u32));
-            carry = (result / 10) as u32;
+impl fmt::Display for ParseMulConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseMulConfigError::InvalidLine { line } =>
+                write!(f, "expected key = value on line {}", line),
+            ParseMulConfigError::UnknownKey { line, ref key } =>
+                write!(f, "unknown key {:?} on line {}", key, line),
+            ParseMulConfigError::InvalidValue { line, ref value } =>
+                write!(f, "invalid value {:?} on line {}", value, line),
+            ParseMulConfigError::InvalidThreshold { ref key, value, minimum } =>
+                write!(f, "{} = {} is below its minimum of {}", key, value, minimum),
         }
+    }
+}
 
-        while carry > 0 {
-            line_str.push(digit_to_char(carry));
-            carry = carry / 10;
+impl Error for ParseMulConfigError {}
+
+// Parts use every bit of a Limb, carries come from overflowing adds
+// and the high half of a DoubleLimb
+pub const LIMB_BITS: u32 = 32;
+pub const BASE: DoubleLimb = 1 << LIMB_BITS;
+
+pub fn from_string(input_str: &str) -> Result<Bignum, ParseBignumError> {
+    parse_radix(input_str, 10, false, false)
+}
+
+pub fn shift_left(a: &mut Bignum, num_places: usize) {
+    // TODO: Possibly impl as a << trait?
+    if a.magnitude.parts.is_empty() {
+        return;
+    }
+    a.magnitude.parts.splice(0..0, iter::repeat_n(0, num_places));
+    a.check_invariants();
+}
+
+impl Bignum {
+    /// Builds a Bignum from limbs, least significant first, checking
+    /// that the top limb is nonzero and that zero is not Negative
+    pub fn from_parts(sign: Sign, limbs: Vec<Limb>) -> Result<Bignum, InvalidLimbError> {
+        if limbs.last() == Some(&0) {
+            return Err(InvalidLimbError::LeadingZero { index: limbs.len() - 1 });
         }
-        
-        line_str = line_str.chars().rev().collect::<String>();
-        // Move result forward
-        for _ in 0..i-1 {
-            line_str.push('0');
+        if limbs.is_empty() && sign == Negative {
+            return Err(InvalidLimbError::NegativeZero);
         }
+        Ok(Bignum::from_raw(sign, limbs))
+    }
 
-        string_add(&mut temp_str, &line_str);
+    /// Like from_parts, but the invariants are only checked in debug builds
+    pub fn from_parts_unchecked(sign: Sign, limbs: Vec<Limb>) -> Bignum {
+        let result = Bignum::from_raw(sign, limbs);
+        result.check_invariants();
+        result
     }
 
-    left.clone_from(&skip_leading_zeroes(&temp_str).to_string());
-    left
-}
+    /// Attaches a sign to a magnitude; zero stays Nonnegative whatever the sign
+    pub fn from_sign_magnitude(sign: Sign, magnitude: BigUint) -> Bignum {
+        let sign = if magnitude.is_zero() { Nonnegative } else { sign };
+        Bignum { sign, magnitude }
+    }
 
-pub fn from_string(input_str: &str) -> Result<Bignum, ParseBignumError> {
-    if input_str.is_empty() {
-        Err(ParseBignumError)
+    pub fn into_sign_magnitude(self) -> (Sign, BigUint) {
+        (self.sign, self.magnitude)
     }
-    else if input_str.starts_with("-") {
-        string_to_parts(&input_str[1..]).map(|parts| Bignum {
-            parts: parts,
-            sign: Negative,
-        })
+
+    /// The limbs, least significant first, with no leading zero limbs
+    pub fn limbs(&self) -> &[Limb] {
+        &self.magnitude.parts
     }
-    else {
-        string_to_parts(&input_str[..]).map(|parts| Bignum {
-            parts: parts,
-            sign: Nonnegative,
-        })
+
+    pub fn sign(&self) -> Sign {
+        self.sign
     }
-}
 
-fn string_to_parts(input_string: &str) -> Result<Vec<u32>, ParseBignumError> {
-    let s = skip_leading_zeroes(input_string);
-    let mut parts = Vec::with_capacity(s.len());
+    pub fn magnitude(&self) -> &BigUint {
+        &self.magnitude
+    }
 
-    let mut quotient: String = s.to_string();
+    pub fn view(&self) -> BignumRef<'_> {
+        BignumRef { parts: &self.magnitude.parts, sign: self.sign }
+    }
 
-    while quotient != "0" {
-        // Repeated long division by BASE
-        let mut next = String::with_capacity(quotient.len());
-        let mut carry: u64 = 0;
-        
-        for c in quotient.chars() {
-            let digit = char_to_digit(c);
-            carry = carry * 10 + digit as u64;
+    // No checks at all, for intermediate values that may not be canonical
+    pub(crate) fn from_raw(sign: Sign, parts: Vec<Limb>) -> Bignum {
+        Bignum { sign, magnitude: BigUint { parts } }
+    }
 
-            // TODO: Don't do the char conversion every time
-            next.push(digit_to_char((carry / BASE as u64) as u32));
-            carry = carry % BASE as u64;
+    // Brings self into canonical form: no leading zero parts, and a zero is Nonnegative
+    pub(crate) fn normalize(&mut self) {
+        self.magnitude.normalize();
+        if self.magnitude.is_zero() {
+            self.sign = Nonnegative;
         }
-        quotient = skip_leading_zeroes(&next).to_string();
-        parts.push(carry as u32);
     }
-    Ok(parts)
-}
 
-pub fn shift_left(a: &mut Bignum, num_places: usize) {
-    // TODO: Possibly impl as a << trait?
-    let mut zeroes = vec![0; num_places];
-    zeroes.append(&mut a.parts);
-    a.parts = zeroes;
+    // Called on the result of every public operation, a no-op in release builds
+    pub(crate) fn check_invariants(&self) {
+        self.magnitude.check_invariants();
+        debug_assert!(!self.magnitude.is_zero() || self.sign == Nonnegative, "bignum is a negative zero");
+    }
 }
 
-impl Bignum {
-    pub fn normalize(&mut self) {
-        let mut last_non_zero_index = 0;
-        let num_parts = self.parts.len();
-        for i in 0..num_parts {
-            let index_to_test = num_parts - i - 1;
-            if self.parts[index_to_test] != 0 {
-                last_non_zero_index = index_to_test;
-                break;
-            }
+impl<'a> BignumRef<'a> {
+    /// Views limbs, least significant first, with the same checks as Bignum::from_parts
+    pub fn from_parts(sign: Sign, limbs: &'a [Limb]) -> Result<BignumRef<'a>, InvalidLimbError> {
+        if limbs.last() == Some(&0) {
+            return Err(InvalidLimbError::LeadingZero { index: limbs.len() - 1 });
         }
-        if last_non_zero_index != num_parts - 1 {
-            self.parts.truncate(last_non_zero_index + 1);
+        if limbs.is_empty() && sign == Negative {
+            return Err(InvalidLimbError::NegativeZero);
         }
+        Ok(BignumRef { parts: limbs, sign })
     }
-    
-    pub fn to_string(&self) -> String {
-        let mut prefix: String = match self.sign {
-            Negative => "-".to_string(),
-            Nonnegative => "".to_string(),
+
+    pub fn limbs(&self) -> &'a [Limb] {
+        self.parts
+    }
+
+    pub fn sign(&self) -> Sign {
+        self.sign
+    }
+
+    pub fn to_bignum(&self) -> Bignum {
+        Bignum::from_raw(self.sign, self.parts.to_vec())
+    }
+
+    pub(crate) fn negated(self) -> BignumRef<'a> {
+        let sign = match self.sign {
+            Negative => Nonnegative,
+            Nonnegative if self.parts.is_empty() => Nonnegative,
+            Nonnegative => Negative,
         };
+        BignumRef { parts: self.parts, sign }
+    }
+}
 
-        let rest = self.parts.iter().rev();
+impl<'a> From<&'a Bignum> for BignumRef<'a> {
+    fn from(a: &'a Bignum) -> BignumRef<'a> {
+        a.view()
+    }
+}
 
-        // Repeatedly multiply by 64
-        let mut product: String = String::from("0");
+impl FromStr for Bignum {
+    type Err = ParseBignumError;
 
-        for part in rest {
-            let mut next: String = String::with_capacity(2);
-            next.push_str(&part.to_string());
+    fn from_str(s: &str) -> Result<Bignum, ParseBignumError> {
+        from_string(s)
+    }
+}
 
-            string_mult(&mut product, BASE_STR);
-            string_add(&mut product, &next);
-        }
+impl fmt::Display for Bignum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(self.sign == Nonnegative, "", &parts_to_decimal(&self.magnitude.parts, false))
+    }
+}
 
-        prefix.push_str(&product);
-        prefix
+impl fmt::Debug for Bignum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Bignum({})", self)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::string_add;
-    use super::string_mult;
     use super::from_string;
+    use super::Bignum;
+    use super::ParseBignumError;
     use super::shift_left;
-    use super::BASE_STR;
     
-    #[test]
-    fn string_add_test() {
-        assert_eq!(string_add(&mut "123".to_string(), "123"), "246");
-        assert_eq!(string_add(&mut "123".to_string(), "0"), "123");
-        assert_eq!(string_add(&mut "123".to_string(), "10000"), "10123");
-        assert_eq!(string_add(&mut "123456789".to_string(), "987654321"), "1111111110");
-    }
-
-    #[test]
-    fn string_mult_test() {
-        assert_eq!(string_mult(&mut "3".to_string(), "3"), "9");
-        assert_eq!(string_mult(&mut "0".to_string(), "999"), "0");
-        assert_eq!(string_mult(&mut "123".to_string(), "241"), "29643");
-        assert_eq!(string_mult(&mut "349".to_string(), "807"), "281643");
-        assert_eq!(string_mult(&mut "55555".to_string(), "66666"), "3703629630");
-    }
-
     #[test]
     fn type_conversion_test() {
         let examples = vec!(
@@ -268,6 +317,9 @@ mod tests {
             "11111111111111111111",
             "-952892589210459282926222035",
             "12193263132251181129",
+            "4294967295",
+            "-4294967296",
+            "18446744073709551616",
         );
         for string_rep in examples {
             let big = from_string(string_rep).unwrap();
@@ -275,25 +327,56 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_error_test() {
+        assert_eq!(from_string(""), Err(ParseBignumError::Empty));
+        assert_eq!(from_string("-"), Err(ParseBignumError::SignOnly));
+        assert_eq!(from_string("+"), Err(ParseBignumError::SignOnly));
+        assert_eq!(from_string("12a3"), Err(ParseBignumError::InvalidDigit { index: 2, char: 'a' }));
+        assert_eq!(from_string("-12 3"), Err(ParseBignumError::InvalidDigit { index: 3, char: ' ' }));
+        assert_eq!(from_string("--1"), Err(ParseBignumError::InvalidDigit { index: 1, char: '-' }));
+        assert_eq!(from_string("1\u{e9}"), Err(ParseBignumError::InvalidDigit { index: 1, char: '\u{e9}' }));
+        assert_eq!(from_string("+123").unwrap().to_string(), "123");
+        assert_eq!(ParseBignumError::InvalidDigit { index: 2, char: 'a' }.to_string(),
+                   "invalid digit 'a' at index 2");
+    }
+
+    #[test]
+    fn formatting_test() {
+        let positive: Bignum = "12193263132251181129".parse().unwrap();
+        let negative: Bignum = "-9877".parse().unwrap();
+        let zero: Bignum = "-000".parse().unwrap();
+
+        assert_eq!(format!("{}", positive), "12193263132251181129");
+        assert_eq!(format!("{}", negative), "-9877");
+        assert_eq!(format!("{}", zero), "0");
+        assert_eq!(format!("{:+}", positive), "+12193263132251181129");
+        assert_eq!(format!("{:+}", negative), "-9877");
+        assert_eq!(format!("{:8}", negative), "   -9877");
+        assert_eq!(format!("{:<8}|", negative), "-9877   |");
+        assert_eq!(format!("{:*^9}", negative), "**-9877**");
+        assert_eq!(format!("{:08}", negative), "-0009877");
+        assert_eq!(format!("{:+08}", 9877), format!("{:+08}", "9877".parse::<Bignum>().unwrap()));
+        assert_eq!(format!("{:?}", negative), "Bignum(-9877)");
+        assert_eq!("12a".parse::<Bignum>(), Err(ParseBignumError::InvalidDigit { index: 2, char: 'a' }));
+    }
+
     #[test]
     fn equality_test() {
         assert!(from_string("123").unwrap() == from_string("123").unwrap());
         assert!(from_string("123").unwrap() != from_string("-123").unwrap());
         assert!(from_string("123").unwrap() != from_string("124").unwrap());
-        // TODO: check for leading zeroes
+        assert!(from_string("-000").unwrap() == from_string("0").unwrap());
+        assert!(from_string("-0").unwrap().magnitude.parts.is_empty());
+        assert!(from_string("007").unwrap() == from_string("7").unwrap());
     }
 
     #[test]
     fn shift_left_test() {
-        let num: u64 = 123;
-        let mut b = from_string(&num.to_string()).unwrap();
+        let mut b = Bignum::from(123u8);
         shift_left(&mut b, 3); // multiply by base 3 times
 
-        // Less ugly way to do this?
-        let mut str = num.to_string();
-        let mut once = string_mult(&mut str, BASE_STR).to_string();
-        let mut twice = string_mult(&mut once, BASE_STR).to_string();
-        let thrice = string_mult(&mut twice, BASE_STR).to_string();
-        assert_eq!(b.to_string(), thrice.to_owned());
+        // 123 * 2^96
+        assert_eq!(b.to_string(), "9745063989254513524005905891328");
     }
 }
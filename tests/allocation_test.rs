@@ -0,0 +1,51 @@
+extern crate bignum;
+
+use bignum::types::*;
+use bignum::karatsuba::*;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+// Counts the allocations made on each thread, so that tests running
+// alongside each other don't show up in one another's counts
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn count_allocations<F: FnOnce()>(f: F) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn karatsuba_scratch_allocation_test() {
+    for &(cutoff, num_nines) in &[(50, 1000), (20, 5000), (50, 5000)] {
+        let a = from_string(&"9".repeat(num_nines)).unwrap();
+        let b = a.clone();
+        let n = a.limbs().len();
+        let mut out = vec![0; 2 * n];
+        let mut scratch = vec![0; karatsuba_scratch_len(n)];
+
+        // The owned version allocates its output and its scratch space every time
+        let owned = count_allocations(|| { bignum_karatsuba_mult(&a, &b, cutoff, false); });
+        let in_place = count_allocations(|| karatsuba_mult_into(a.limbs(), b.limbs(), &mut out, &mut scratch, cutoff));
+        assert_eq!(in_place, 0);
+        assert!(owned > in_place);
+    }
+}
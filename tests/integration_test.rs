@@ -1,9 +1,20 @@
 extern crate bignum;
 
 use bignum::types::*;
+use bignum::biguint::*;
 use bignum::basic_ops::*;
 use bignum::karatsuba::*;
+use bignum::toom_cook::*;
+use bignum::ntt::*;
+use bignum::mul::*;
+use bignum::parallel::*;
+use bignum::burnikel_ziegler::*;
+use bignum::newton::*;
+use bignum::decimal::*;
+use bignum::limbs;
 use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::collections::{BTreeMap, HashMap};
 
 fn try_with_strs(f: fn(&Bignum, &Bignum) -> Bignum, a1: &str, a2: &str) -> String {
     let b1 = from_string(a1).unwrap();
@@ -11,6 +22,19 @@ fn try_with_strs(f: fn(&Bignum, &Bignum) -> Bignum, a1: &str, a2: &str) -> Strin
     f(&b1, &b2).to_string()
 }
 
+// The basic ops are generic over views, so they need pinning down to plain references
+fn add_wrapper(a: &Bignum, b: &Bignum) -> Bignum {
+    bignum_add(a, b)
+}
+
+fn sub_wrapper(a: &Bignum, b: &Bignum) -> Bignum {
+    bignum_sub(a, b)
+}
+
+fn long_mult_wrapper(a: &Bignum, b: &Bignum) -> Bignum {
+    bignum_long_mult(a, b)
+}
+
 #[test]
 fn comparison_test() {
     let b1 = from_string("123").unwrap();
@@ -32,48 +56,57 @@ fn comparison_test() {
 fn normalization_test() {
     let parts = vec![0, 0, 1, 2, 3];
     let mut denormalized = parts.clone();
-    for _ in 0..5 {
-        denormalized.push(0);
-    }
-    let b1 = Bignum {
-        sign: Sign::Nonnegative,
-        parts: parts,
-    };
-    let mut b2 = Bignum {
-        sign: Sign::Nonnegative,
-        parts: denormalized,
-    };
-    b2.normalize();
-    assert_eq!(b1, b2);
+    denormalized.extend(vec![0; 5]);
+    let b1 = Bignum::from_parts(Sign::Nonnegative, parts.clone()).unwrap();
+    assert_eq!(b1.limbs(), &parts[..]);
+    assert_eq!(b1.sign(), Sign::Nonnegative);
+    assert_eq!(b1, Bignum::from_parts_unchecked(Sign::Nonnegative, parts));
+
+    assert_eq!(Bignum::from_parts(Sign::Nonnegative, denormalized),
+               Err(InvalidLimbError::LeadingZero { index: 9 }));
+    assert_eq!(Bignum::from_parts(Sign::Negative, vec![0]), Err(InvalidLimbError::LeadingZero { index: 0 }));
+    assert_eq!(Bignum::from_parts(Sign::Negative, vec![]), Err(InvalidLimbError::NegativeZero));
+    assert_eq!(Bignum::from_parts(Sign::Nonnegative, vec![]), Ok(from_string("0").unwrap()));
+    assert_eq!(InvalidLimbError::LeadingZero { index: 9 }.to_string(), "most significant limb at index 9 is zero");
+}
+
+#[test]
+#[should_panic(expected = "bignum has a leading zero limb")]
+#[cfg(debug_assertions)]
+fn unchecked_invariants_test() {
+    Bignum::from_parts_unchecked(Sign::Nonnegative, vec![1, 0]);
 }
 
 #[test]
 fn bignum_add_test() {
-    assert_eq!(try_with_strs(bignum_add, "123", "123"), "246");
-    assert_eq!(try_with_strs(bignum_add, "123", "0"), "123");
-    assert_eq!(try_with_strs(bignum_add, "123", "10000"), "10123");
-    assert_eq!(try_with_strs(bignum_add, "123456789", "987654321"), "1111111110");
-    assert_eq!(try_with_strs(bignum_add, "3124679846169848946416687981", "4864789415649194764186476"),
+    assert_eq!(try_with_strs(add_wrapper, "123", "123"), "246");
+    assert_eq!(try_with_strs(add_wrapper, "123", "0"), "123");
+    assert_eq!(try_with_strs(add_wrapper, "123", "10000"), "10123");
+    assert_eq!(try_with_strs(add_wrapper, "123456789", "987654321"), "1111111110");
+    assert_eq!(try_with_strs(add_wrapper, "-123", "-123"), "-246");
+    assert_eq!(try_with_strs(add_wrapper, "-1", "2"), "1");
+    assert_eq!(try_with_strs(add_wrapper, "-2", "1"), "-1");
+    assert_eq!(try_with_strs(add_wrapper, "3124679846169848946416687981", "4864789415649194764186476"),
                "3129544635585498141180874457");
 }
 
 #[test]
 fn bignum_sub_test() {
-    assert_eq!(try_with_strs(bignum_sub, "123", "123"), "0");
-    assert_eq!(try_with_strs(bignum_sub, "123", "0"), "123");
-    assert_eq!(try_with_strs(bignum_sub, "123", "10000"), "-9877");
-    assert_eq!(try_with_strs(bignum_sub, "123456789", "987654321"), "-864197532");
-    assert_eq!(try_with_strs(bignum_sub, "3124679846169848946416687981", "4864789415649194764186476"),
+    assert_eq!(try_with_strs(sub_wrapper, "123", "123"), "0");
+    assert_eq!(try_with_strs(sub_wrapper, "123", "0"), "123");
+    assert_eq!(try_with_strs(sub_wrapper, "123", "10000"), "-9877");
+    assert_eq!(try_with_strs(sub_wrapper, "123456789", "987654321"), "-864197532");
+    assert_eq!(try_with_strs(sub_wrapper, "3124679846169848946416687981", "4864789415649194764186476"),
                "3119815056754199751652501505");
 }
 
 #[test]
 fn long_mult_test() {
-    assert_eq!(try_with_strs(bignum_long_mult, "2", "2"), "4");
-    assert_eq!(try_with_strs(bignum_long_mult, "-2", "2"), "-4");
-    assert_eq!(try_with_strs(bignum_long_mult, "-2", "-2"), "4");
-    assert_eq!(try_with_strs(bignum_long_mult, "123456789", "987654321"), "121932631112635269");
-    assert_eq!(try_with_strs(bignum_long_mult, "3124679846169848946416687981", "4864789415649194764186476"),
+    assert_eq!(try_with_strs(long_mult_wrapper, "2", "2"), "4");
+    assert_eq!(try_with_strs(long_mult_wrapper, "-2", "2"), "-4");
+    assert_eq!(try_with_strs(long_mult_wrapper, "-2", "-2"), "4");
+    assert_eq!(try_with_strs(long_mult_wrapper, "123456789", "987654321"), "121932631112635269");
+    assert_eq!(try_with_strs(long_mult_wrapper, "3124679846169848946416687981", "4864789415649194764186476"),
                "15200909442939435242569275059005520266618929791944956");
 }
 
@@ -104,3 +137,726 @@ fn bignum_karatsuba_mult_parallel_test() {
     assert_eq!(try_with_strs(karatsuba_par_wrapper, "3124679846169848946416687981", "4864789415649194764186476"),
                "15200909442939435242569275059005520266618929791944956");
 }
+
+fn toom3_wrapper(a: &Bignum, b: &Bignum) -> Bignum {
+    bignum_toom3_mult(a, b, 4, 2, false)
+}
+
+fn toom4_wrapper(a: &Bignum, b: &Bignum) -> Bignum {
+    bignum_toom4_mult(a, b, 4, 2, true)
+}
+
+// Toom-4 pieces of 100 parts or fewer go through Toom-3 before Karatsuba multiplication
+fn toom_wrapper(a: &Bignum, b: &Bignum) -> Bignum {
+    bignum_toom_mult(a, b, 100, 4, 2, false)
+}
+
+#[test]
+fn toom_cook_mult_test() {
+    for wrapper in &[toom3_wrapper as fn(&Bignum, &Bignum) -> Bignum, toom4_wrapper, toom_wrapper] {
+        assert_eq!(try_with_strs(*wrapper, "2", "2"), "4");
+        assert_eq!(try_with_strs(*wrapper, "-2", "2"), "-4");
+        assert_eq!(try_with_strs(*wrapper, "1234567891", "9876543219"), "12193263132251181129");
+        assert_eq!(try_with_strs(*wrapper, &"9".repeat(500), &"9".repeat(500)),
+                   format!("{}8{}1", "9".repeat(499), "0".repeat(499)));
+        assert_eq!(try_with_strs(*wrapper, &format!("-{}", "9".repeat(500)), &"3".repeat(400)),
+                   try_with_strs(long_mult_wrapper, &format!("-{}", "9".repeat(500)), &"3".repeat(400)));
+    }
+
+    let a = from_string(&"123456789".repeat(60)).unwrap();
+    let b = from_string(&"987654321".repeat(50)).unwrap();
+    let expected = bignum_long_mult(&a, &b);
+    assert_eq!(biguint_toom3_mult(a.magnitude(), b.magnitude(), 5, 3, true), *expected.magnitude());
+    assert_eq!(biguint_toom4_mult(a.magnitude(), b.magnitude(), 5, 3, false), *expected.magnitude());
+    assert_eq!(biguint_toom_mult(a.magnitude(), b.magnitude(), 20, 5, 3, true), *expected.magnitude());
+
+    // Short enough for Karatsuba multiplication all the way
+    let c = from_string("-12345678901234567890").unwrap();
+    assert_eq!(bignum_toom3_mult(&a, &c, 4, 2, false), bignum_long_mult(&a, &c));
+}
+
+#[test]
+fn lopsided_mult_test() {
+    // The long side splits into blocks of the short side's length, the last one partial
+    let long = from_string(&format!("-{}", "9".repeat(10000))).unwrap();
+    let short = from_string(&"9".repeat(600)).unwrap();
+    let expected = format!("-{}8{}{}1", "9".repeat(599), "9".repeat(9400), "0".repeat(599));
+    assert_eq!(bignum_long_mult(&long, &short).to_string(), expected);
+    assert_eq!(bignum_karatsuba_mult(&long, &short, 8, false).to_string(), expected);
+    assert_eq!(bignum_karatsuba_mult(&short, &long, 8, true).to_string(), expected);
+    assert_eq!(bignum_toom3_mult(&long, &short, 12, 4, false).to_string(), expected);
+    assert_eq!(bignum_toom4_mult(&short, &long, 12, 4, true).to_string(), expected);
+
+    // Zero blocks in the middle of the long side
+    let gappy = from_string(&format!("1{}1", "0".repeat(3000))).unwrap();
+    let expected = bignum_long_mult(&gappy, &short);
+    assert_eq!(bignum_karatsuba_mult(&gappy, &short, 4, false), expected);
+    assert_eq!(bignum_toom4_mult(&short, &gappy, 8, 4, false), expected);
+}
+
+fn ntt_wrapper(a: &Bignum, b: &Bignum) -> Bignum {
+    bignum_ntt_mult(a, b, false)
+}
+
+fn ntt_par_wrapper(a: &Bignum, b: &Bignum) -> Bignum {
+    bignum_ntt_mult(a, b, true)
+}
+
+#[test]
+fn ntt_mult_test() {
+    for wrapper in &[ntt_wrapper as fn(&Bignum, &Bignum) -> Bignum, ntt_par_wrapper] {
+        assert_eq!(try_with_strs(*wrapper, "2", "2"), "4");
+        assert_eq!(try_with_strs(*wrapper, "-2", "2"), "-4");
+        assert_eq!(try_with_strs(*wrapper, "0", "-2"), "0");
+        assert_eq!(try_with_strs(*wrapper, "1234567891", "9876543219"), "12193263132251181129");
+        assert_eq!(try_with_strs(*wrapper, "3124679846169848946416687981", "4864789415649194764186476"),
+                   "15200909442939435242569275059005520266618929791944956");
+        assert_eq!(try_with_strs(*wrapper, &"9".repeat(3000), &"9".repeat(3000)),
+                   format!("{}8{}1", "9".repeat(2999), "0".repeat(2999)));
+    }
+
+    // Every limb at its largest makes every coefficient, and so every carry, as large as it gets
+    let max = Bignum::from_parts(Sign::Nonnegative, vec![Limb::MAX; 2000]).unwrap();
+    let lopsided = Bignum::from_parts(Sign::Negative, vec![Limb::MAX; 3]).unwrap();
+    assert_eq!(bignum_ntt_mult(&max, &max, true), bignum_karatsuba_mult(&max, &max.clone(), 20, false));
+    assert_eq!(bignum_ntt_mult(&lopsided, &max, false), bignum_long_mult(&lopsided, &max));
+    assert_eq!(biguint_ntt_mult(max.magnitude(), lopsided.magnitude(), false), *bignum_long_mult(&max, &lopsided).magnitude());
+}
+
+#[test]
+fn mul_dispatch_test() {
+    // Small thresholds, so that every algorithm gets a turn
+    let config = MulConfig { karatsuba_threshold: 4, toom3_threshold: 8, toom4_threshold: 16, ntt_threshold: 32,
+                             parallel: true, parallel_depth: 3, parallel_grain: 6 };
+    for &len in &[0, 1, 4, 5, 8, 9, 16, 17, 32, 33, 100] {
+        let a = Bignum::from_parts(Sign::Negative, vec![Limb::MAX; len + 1]).unwrap();
+        let b = Bignum::from_parts(Sign::Nonnegative, (0..len as Limb).map(|i| i.wrapping_mul(0x9e37_79b9)).chain(Some(1)).collect()).unwrap();
+        let expected = bignum_long_mult(&a, &b);
+        assert_eq!(bignum_mul_with(&a, &b, &config), expected);
+        assert_eq!(bignum_mul_with(&a, &a, &MulConfig { parallel: false, ..config }), bignum_long_mult(&a, &a.clone()));
+        assert_eq!(bignum_mul(&a, &b), expected);
+        assert_eq!(&a * &b, expected);
+        assert_eq!(biguint_mul_with(a.magnitude(), b.magnitude(), &config), *expected.magnitude());
+        assert_eq!(biguint_mul(a.magnitude(), b.magnitude()), *expected.magnitude());
+    }
+
+    assert_eq!(MulConfig::default(), MulConfig::DEFAULT);
+}
+
+#[test]
+fn parallelism_test() {
+    assert_eq!(Parallelism::from(true), Parallelism::TOP_LEVEL);
+    assert_eq!(Parallelism::from(false), Parallelism::SERIAL);
+    let parallel = Parallelism { depth: 2, grain: 100 };
+    assert_eq!(parallel.forks(100), cfg!(feature = "parallel"));
+    assert!(!parallel.forks(99));
+    assert_eq!(parallel.below(), Parallelism { depth: 1, grain: 100 });
+    assert!(!parallel.below().below().forks(1000));
+    assert!(!Parallelism::SERIAL.below().forks(1000));
+    assert_eq!(MulConfig::DEFAULT.parallelism(), Parallelism::SERIAL);
+
+    // Without the parallel feature, this all runs serially
+    let deep = Parallelism { depth: 8, grain: 4 };
+    let a = from_string(&format!("-{}", "9".repeat(3000))).unwrap();
+    let b = from_string(&"7".repeat(2500)).unwrap();
+    let expected = bignum_long_mult(&a, &b);
+    assert_eq!(bignum_karatsuba_mult(&a, &b, 4, deep), expected);
+    assert_eq!(bignum_toom4_mult(&b, &a, 8, 4, deep), expected);
+    assert_eq!(bignum_ntt_mult(&a, &b, deep), expected);
+}
+
+#[test]
+#[cfg(feature = "parallel")]
+fn thread_pool_test() {
+    // Deep enough that every level forks, on a pool of its own
+    let pool = ThreadPool::new(Configuration::new().set_num_threads(2)).unwrap();
+    let deep = Parallelism { depth: 8, grain: 4 };
+    let a = from_string(&format!("-{}", "9".repeat(3000))).unwrap();
+    let b = from_string(&"7".repeat(2500)).unwrap();
+    let expected = bignum_long_mult(&a, &b);
+    pool.install(|| {
+        assert_eq!(bignum_karatsuba_mult(&a, &b, 4, deep), expected);
+        assert_eq!(bignum_karatsuba_sqr(&a, 4, deep), bignum_long_mult(&a, &a.clone()));
+        assert_eq!(bignum_toom3_mult(&a, &b, 8, 4, deep), expected);
+        assert_eq!(bignum_toom4_mult(&b, &a, 8, 4, deep), expected);
+        assert_eq!(bignum_ntt_mult(&a, &b, deep), expected);
+    });
+
+    let config = MulConfig { karatsuba_threshold: 8, toom3_threshold: 40, toom4_threshold: 80, ntt_threshold: 400,
+                             parallel: true, parallel_depth: 4, parallel_grain: 16 };
+    assert_eq!(bignum_mul_in(&pool, &a, &b, &config), expected);
+    assert_eq!(bignum_mul_in(&pool, &a, &b, &MulConfig { ntt_threshold: 1000, ..config }), expected);
+}
+
+#[test]
+fn mul_global_config_test() {
+    // Other tests may multiply meanwhile, which only changes how they get their answers
+    let config = MulConfig { karatsuba_threshold: 2, toom3_threshold: 6, ..MulConfig::DEFAULT };
+    assert_eq!(MulConfig::set_global(config), Ok(()));
+    assert_eq!(MulConfig::global(), config);
+    assert_eq!(try_with_strs(|a, b| a * b, &"9".repeat(200), &"9".repeat(200)),
+               format!("{}8{}1", "9".repeat(199), "0".repeat(199)));
+
+    // A config the algorithms would panic on is turned away, and the old one stays
+    let invalid = MulConfig { karatsuba_threshold: 1, ..MulConfig::DEFAULT };
+    assert_eq!(MulConfig::set_global(invalid),
+               Err(ParseMulConfigError::InvalidThreshold { key: "karatsuba_threshold".to_string(), value: 1, minimum: 2 }));
+    assert_eq!(MulConfig::global(), config);
+    MulConfig::set_global(MulConfig::DEFAULT).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "invalid MulConfig: toom3_threshold = 3 is below its minimum of 4")]
+fn mul_invalid_config_test() {
+    let a = from_string(&"9".repeat(200)).unwrap();
+    bignum_mul_with(&a, &a, &MulConfig { karatsuba_threshold: 2, toom3_threshold: 3, ..MulConfig::DEFAULT });
+}
+
+#[test]
+fn mul_config_parse_test() {
+    let config = MulConfig { karatsuba_threshold: 40, toom3_threshold: 810, toom4_threshold: 1216,
+                             ntt_threshold: 5062, parallel: true, parallel_depth: 2, parallel_grain: 64 };
+    assert_eq!(config.to_string().parse(), Ok(config));
+
+    let text = "# measured by bignum-tune\n\n  toom3_threshold=900  # comment\nparallel = true\n";
+    assert_eq!(text.parse(), Ok(MulConfig { toom3_threshold: 900, parallel: true, ..MulConfig::DEFAULT }));
+    assert_eq!("".parse(), Ok(MulConfig::DEFAULT));
+
+    assert_eq!("parallel = true\nntt_threshold 12".parse::<MulConfig>(),
+               Err(ParseMulConfigError::InvalidLine { line: 2 }));
+    assert_eq!("fft_threshold = 12".parse::<MulConfig>(),
+               Err(ParseMulConfigError::UnknownKey { line: 1, key: "fft_threshold".to_string() }));
+    assert_eq!("\nkaratsuba_threshold = -1".parse::<MulConfig>(),
+               Err(ParseMulConfigError::InvalidValue { line: 2, value: "-1".to_string() }));
+    assert_eq!("parallel = yes".parse::<MulConfig>(),
+               Err(ParseMulConfigError::InvalidValue { line: 1, value: "yes".to_string() }));
+
+    // Thresholds the algorithms would panic on, and ones out of order
+    let invalid = |key: &str, value, minimum| Err(ParseMulConfigError::InvalidThreshold { key: key.to_string(), value, minimum });
+    assert_eq!("karatsuba_threshold = 1".parse::<MulConfig>(), invalid("karatsuba_threshold", 1, 2));
+    assert_eq!("karatsuba_threshold = 2\ntoom3_threshold = 3".parse::<MulConfig>(), invalid("toom3_threshold", 3, 4));
+    assert_eq!("karatsuba_threshold = 2\ntoom3_threshold = 4\ntoom4_threshold = 5".parse::<MulConfig>(),
+               Ok(MulConfig { karatsuba_threshold: 2, toom3_threshold: 4, toom4_threshold: 5, ..MulConfig::DEFAULT }));
+    assert_eq!("toom3_threshold = 50".parse::<MulConfig>(), invalid("toom3_threshold", 50, 51));
+    assert_eq!("toom4_threshold = 1000".parse::<MulConfig>(), invalid("toom4_threshold", 1000, 1501));
+    assert_eq!("ntt_threshold = 3000".parse::<MulConfig>(), invalid("ntt_threshold", 3000, 3001));
+    assert_eq!(invalid("toom3_threshold", 3, 4).unwrap_err().to_string(), "toom3_threshold = 3 is below its minimum of 4");
+}
+
+#[test]
+fn sqr_test() {
+    let nines = from_string(&"9".repeat(300)).unwrap();
+    let expected = format!("{}8{}1", "9".repeat(299), "0".repeat(299));
+    assert_eq!(bignum_sqr(&nines).to_string(), expected);
+    assert_eq!(bignum_sqr(&-&nines).to_string(), expected);
+    assert_eq!(bignum_karatsuba_sqr(&nines, 2, false).to_string(), expected);
+    assert_eq!(bignum_karatsuba_sqr(&-&nines, 4, true).to_string(), expected);
+    assert_eq!(biguint_sqr(nines.magnitude()), biguint_karatsuba_sqr(nines.magnitude(), 3, false));
+
+    // Multiplying a number by itself picks the squaring routines
+    assert_eq!(bignum_long_mult(&nines, &nines).to_string(), expected);
+    assert_eq!(bignum_karatsuba_mult(&nines, &nines, 2, true).to_string(), expected);
+    assert_eq!((&nines * &nines).to_string(), expected);
+
+    // Every limb at its largest maximizes the carries
+    let max = Bignum::from_parts(Sign::Negative, vec![Limb::MAX; 7]).unwrap();
+    let one = Bignum::from(1u8);
+    let base_7 = Bignum::from_parts(Sign::Nonnegative, [vec![0; 7], vec![1]].concat()).unwrap();
+    assert_eq!(bignum_sqr(&max), bignum_long_mult(&(&base_7 - &one), &(&base_7 - &one)));
+    assert_eq!(bignum_sqr(&from_string("0").unwrap()), from_string("0").unwrap());
+    assert_eq!(bignum_karatsuba_sqr(&from_string("-1").unwrap(), 2, false), one);
+}
+
+#[test]
+fn karatsuba_scratch_test() {
+    let a = from_string(&"9".repeat(1000)).unwrap();
+    let b = from_string(&"7".repeat(600)).unwrap();
+    let (p, q) = (a.limbs().len(), b.limbs().len());
+
+    // Dirty buffers of exactly the advertised sizes, reused across calls
+    let mut out = vec![Limb::MAX; p + q];
+    let mut scratch = vec![Limb::MAX; karatsuba_scratch_len(p)];
+    for cutoff in 2..6 {
+        karatsuba_mult_into(a.limbs(), b.limbs(), &mut out, &mut scratch, cutoff);
+        let product = Bignum::from_parts(Sign::Nonnegative, out.clone()).unwrap();
+        assert_eq!(product, bignum_long_mult(&a, &b));
+    }
+
+    assert_eq!(karatsuba_scratch_len(3), 0);
+    assert!(karatsuba_scratch_len(1000) <= 4 * 1000 + 4 * 64);
+    let mut out = [Limb::MAX; 5];
+    karatsuba_mult_into(&[1, 2, 3], &[4, 5], &mut out, &mut [], 2);
+    assert_eq!(out, [4, 13, 22, 15, 0]);
+}
+
+#[test]
+#[should_panic(expected = "scratch slice is too short")]
+fn karatsuba_scratch_too_short_test() {
+    let a = vec![1; 100];
+    let mut out = vec![0; 200];
+    let mut scratch = vec![0; karatsuba_scratch_len(100) - 1];
+    karatsuba_mult_into(&a, &a, &mut out, &mut scratch, 2);
+}
+
+type DivremFn = fn(&Bignum, &Bignum) -> Result<(Bignum, Bignum), DivideByZeroError>;
+
+fn try_divrem_with_strs(f: DivremFn, a1: &str, a2: &str) -> (String, String) {
+    let b1 = from_string(a1).unwrap();
+    let b2 = from_string(a2).unwrap();
+    let (q, r) = f(&b1, &b2).unwrap();
+    (q.to_string(), r.to_string())
+}
+
+fn strs(q: &str, r: &str) -> (String, String) {
+    (q.to_string(), r.to_string())
+}
+
+#[test]
+fn bignum_divrem_test() {
+    assert_eq!(try_divrem_with_strs(bignum_divrem, "7", "2"), strs("3", "1"));
+    assert_eq!(try_divrem_with_strs(bignum_divrem, "-7", "2"), strs("-3", "-1"));
+    assert_eq!(try_divrem_with_strs(bignum_divrem, "7", "-2"), strs("-3", "1"));
+    assert_eq!(try_divrem_with_strs(bignum_divrem, "-7", "-2"), strs("3", "-1"));
+    assert_eq!(try_divrem_with_strs(bignum_divrem, "0", "5"), strs("0", "0"));
+    assert_eq!(try_divrem_with_strs(bignum_divrem, "123", "123456789"), strs("0", "123"));
+    assert_eq!(try_divrem_with_strs(bignum_divrem, "-6", "3"), strs("-2", "0"));
+    assert_eq!(try_divrem_with_strs(bignum_divrem, "15200909442939435242569275059005520266618929791944956",
+                                    "4864789415649194764186476"),
+               strs("3124679846169848946416687981", "0"));
+    assert_eq!(try_divrem_with_strs(bignum_divrem, "15200909442939435242569275059005520266618929791944999",
+                                    "3124679846169848946416687981"),
+               strs("4864789415649194764186476", "43"));
+    assert_eq!(try_divrem_with_strs(bignum_divrem, "3999999999999999999999999999", "1999999999999999999"),
+               strs("2000000000", "1999999999"));
+}
+
+#[test]
+fn bignum_divrem_floor_test() {
+    assert_eq!(try_divrem_with_strs(bignum_divrem_floor, "7", "2"), strs("3", "1"));
+    assert_eq!(try_divrem_with_strs(bignum_divrem_floor, "-7", "2"), strs("-4", "1"));
+    assert_eq!(try_divrem_with_strs(bignum_divrem_floor, "7", "-2"), strs("-4", "-1"));
+    assert_eq!(try_divrem_with_strs(bignum_divrem_floor, "-7", "-2"), strs("3", "-1"));
+    assert_eq!(try_divrem_with_strs(bignum_divrem_floor, "-6", "3"), strs("-2", "0"));
+}
+
+#[test]
+fn bignum_divrem_euclid_test() {
+    assert_eq!(try_divrem_with_strs(bignum_divrem_euclid, "7", "2"), strs("3", "1"));
+    assert_eq!(try_divrem_with_strs(bignum_divrem_euclid, "-7", "2"), strs("-4", "1"));
+    assert_eq!(try_divrem_with_strs(bignum_divrem_euclid, "7", "-2"), strs("-3", "1"));
+    assert_eq!(try_divrem_with_strs(bignum_divrem_euclid, "-7", "-2"), strs("4", "1"));
+    assert_eq!(try_divrem_with_strs(bignum_divrem_euclid, "-6", "3"), strs("-2", "0"));
+}
+
+#[test]
+fn divide_by_zero_test() {
+    let a = from_string("123").unwrap();
+    let zero = from_string("-000").unwrap();
+    assert_eq!(bignum_divrem(&a, &zero).unwrap_err(), DivideByZeroError);
+    assert_eq!(bignum_divrem_floor(&a, &zero).unwrap_err(), DivideByZeroError);
+    assert_eq!(bignum_divrem_euclid(&a, &zero).unwrap_err(), DivideByZeroError);
+}
+
+fn burnikel_ziegler_wrapper(a: &Bignum, b: &Bignum) -> Result<(Bignum, Bignum), DivideByZeroError> {
+    bignum_burnikel_ziegler_divrem(a, b, 2, false)
+}
+
+fn burnikel_ziegler_par_wrapper(a: &Bignum, b: &Bignum) -> Result<(Bignum, Bignum), DivideByZeroError> {
+    bignum_burnikel_ziegler_divrem(a, b, 2, true)
+}
+
+fn burnikel_ziegler_deep_wrapper(a: &Bignum, b: &Bignum) -> Result<(Bignum, Bignum), DivideByZeroError> {
+    bignum_burnikel_ziegler_divrem(a, b, 2, Parallelism { depth: 4, grain: 2 })
+}
+
+// Low enough thresholds that the multiplications use every algorithm
+fn burnikel_ziegler_with_wrapper(a: &Bignum, b: &Bignum) -> Result<(Bignum, Bignum), DivideByZeroError> {
+    let config = MulConfig { karatsuba_threshold: 2, toom3_threshold: 4, toom4_threshold: 6, ntt_threshold: 8,
+                             ..MulConfig::DEFAULT };
+    bignum_burnikel_ziegler_divrem_with(a, b, 2, &config)
+}
+
+#[test]
+fn bignum_burnikel_ziegler_divrem_test() {
+    for f in [burnikel_ziegler_wrapper as DivremFn, burnikel_ziegler_par_wrapper, burnikel_ziegler_deep_wrapper,
+              burnikel_ziegler_with_wrapper].iter() {
+        assert_eq!(try_divrem_with_strs(*f, "7", "2"), strs("3", "1"));
+        assert_eq!(try_divrem_with_strs(*f, "-7", "2"), strs("-3", "-1"));
+        assert_eq!(try_divrem_with_strs(*f, "15200909442939435242569275059005520266618929791944956",
+                                        "4864789415649194764186476"),
+                   strs("3124679846169848946416687981", "0"));
+        assert_eq!(try_divrem_with_strs(*f, "-15200909442939435242569275059005520266618929791944999",
+                                        "3124679846169848946416687981"),
+                   strs("-4864789415649194764186476", "-43"));
+        assert_eq!(try_divrem_with_strs(*f, "1234", "-3124679846169848946416687981"), strs("0", "1234"));
+        assert_eq!(try_divrem_with_strs(*f, &"9".repeat(2000), &format!("-{}", "7".repeat(700))),
+                   try_divrem_with_strs(bignum_divrem, &"9".repeat(2000), &format!("-{}", "7".repeat(700))));
+    }
+}
+
+#[test]
+fn bignum_reciprocal_test() {
+    let b = from_string("3").unwrap();
+    assert_eq!(bignum_reciprocal(&b, 1).unwrap().to_string(), "1431655765");
+    assert_eq!(bignum_reciprocal(&b, 2).unwrap().to_string(), "6148914691236517205");
+    let b = from_string("-4000000000").unwrap();
+    assert_eq!(bignum_reciprocal(&b, 1).unwrap().to_string(), "-1");
+    assert_eq!(bignum_reciprocal(&b, 2).unwrap().to_string(), "-4611686018");
+    assert_eq!(bignum_reciprocal(&from_string("0").unwrap(), 2).unwrap_err(), DivideByZeroError);
+}
+
+#[test]
+fn bignum_newton_divrem_test() {
+    let nines = from_string(&"9".repeat(500)).unwrap();
+    let divisor = from_string("-123456789123456789").unwrap();
+    let (q, r) = bignum_newton_divrem(&nines, &divisor).unwrap();
+    assert_eq!((q.clone(), r.clone()), bignum_divrem(&nines, &divisor).unwrap());
+    assert_eq!(bignum_add(&bignum_long_mult(&q, &divisor), &r), nines);
+    assert_eq!(try_divrem_with_strs(bignum_newton_divrem, "-7", "2"), strs("-3", "-1"));
+}
+
+#[test]
+fn operators_test() {
+    let a = from_string("3124679846169848946416687981").unwrap();
+    let b = from_string("-4864789415649194764186476").unwrap();
+
+    assert_eq!((&a + &b).to_string(), "3119815056754199751652501505");
+    assert_eq!((a.clone() + &b).to_string(), "3119815056754199751652501505");
+    assert_eq!((&a + b.clone()).to_string(), "3119815056754199751652501505");
+    assert_eq!((a.clone() + b.clone()).to_string(), "3119815056754199751652501505");
+
+    assert_eq!((&b - &a).to_string(), "-3129544635585498141180874457");
+    assert_eq!((b.clone() - &a).to_string(), "-3129544635585498141180874457");
+    assert_eq!((&b - a.clone()).to_string(), "-3129544635585498141180874457");
+    assert_eq!((b.clone() - a.clone()).to_string(), "-3129544635585498141180874457");
+
+    let product = "-15200909442939435242569275059005520266618929791944956";
+    assert_eq!((&a * &b).to_string(), product);
+    assert_eq!((a.clone() * b.clone()).to_string(), product);
+    assert_eq!((&a * &b / &b).to_string(), a.to_string());
+    assert_eq!((&a % &b).to_string(), "1485041323065907808970389");
+    assert_eq!((-&a).to_string(), "-3124679846169848946416687981");
+    assert_eq!((-b.clone()).to_string(), "4864789415649194764186476");
+
+    let mut c = a.clone();
+    c += &b;
+    c -= b.clone();
+    assert_eq!(c, a);
+    c -= &a;
+    assert_eq!(c.to_string(), "0");
+    c += &a;
+    c *= &b;
+    assert_eq!(c.to_string(), product);
+    c /= b.clone();
+    c %= &from_string("1000").unwrap();
+    assert_eq!(c.to_string(), "981");
+}
+
+#[test]
+#[should_panic]
+fn operators_divide_by_zero_test() {
+    let _ = from_string("1").unwrap() / from_string("0").unwrap();
+}
+
+#[test]
+fn radix_test() {
+    let n = from_string("-3124679846169848946416687981").unwrap();
+    assert_eq!(n.to_str_radix(16), "-a18acb1e07fde0cdf7c1b6d");
+    assert_eq!(n.to_str_radix(36), "-awm3fhkr7x5t4kb3ot");
+    assert_eq!(n.to_str_radix(2).len(), 93);
+    assert_eq!(from_string("0").unwrap().to_str_radix(7), "0");
+
+    for radix in 2..37 {
+        let s = n.to_str_radix(radix);
+        assert_eq!(Bignum::from_str_radix(&s, radix).unwrap(), n);
+        assert_eq!(Bignum::from_str_radix(&s.to_uppercase(), radix).unwrap(), n);
+    }
+
+    assert_eq!(Bignum::from_str_radix("ff", 16).unwrap().to_string(), "255");
+    assert_eq!(Bignum::from_str_radix("1g", 16),
+               Err(ParseBignumError::InvalidDigit { index: 1, char: 'g' }));
+    assert_eq!(Bignum::from_str_radix("0x1f", 16),
+               Err(ParseBignumError::InvalidDigit { index: 1, char: 'x' }));
+    assert_eq!(Bignum::from_str_radix_lenient("-0x1f", 16).unwrap().to_string(), "-31");
+    assert_eq!(Bignum::from_str_radix_lenient("0B101", 2).unwrap().to_string(), "5");
+    assert_eq!(Bignum::from_str_radix_lenient("0o17", 8).unwrap().to_string(), "15");
+    assert_eq!(Bignum::from_str_radix_lenient("0b1", 16).unwrap().to_string(), "177");
+    assert_eq!(Bignum::from_str_radix_lenient("0x", 16),
+               Err(ParseBignumError::InvalidDigit { index: 1, char: 'x' }));
+    assert_eq!(Bignum::from_str_radix_lenient("0x1z", 16),
+               Err(ParseBignumError::InvalidDigit { index: 3, char: 'z' }));
+}
+
+#[test]
+fn radix_formatting_test() {
+    let n = from_string("-3124679846169848946416687981").unwrap();
+    let m = from_string("255").unwrap();
+
+    assert_eq!(format!("{:x}", n), "-a18acb1e07fde0cdf7c1b6d");
+    assert_eq!(format!("{:#X}", n), "-0xA18ACB1E07FDE0CDF7C1B6D");
+    assert_eq!(format!("{:#x}", m), "0xff");
+    assert_eq!(format!("{:#010x}", m), "0x000000ff");
+    assert_eq!(format!("{:o}", m), "377");
+    assert_eq!(format!("{:#o}", m), "0o377");
+    assert_eq!(format!("{:b}", m), "11111111");
+    assert_eq!(format!("{:#b}", m), "0b11111111");
+    assert_eq!(format!("{:>12b}", m), "    11111111");
+    assert_eq!(format!("{:x}", from_string("0").unwrap()), "0");
+}
+
+#[test]
+fn decimal_conversion_test() {
+    let nines = "9".repeat(5000);
+    let mut power_of_ten = "1".to_string();
+    power_of_ten.push_str(&"0".repeat(5000));
+
+    for parallel in [false, true].iter() {
+        let a = bignum_from_decimal(&nines, *parallel).unwrap();
+        let b = bignum_from_decimal(&power_of_ten, *parallel).unwrap();
+        assert_eq!(&b - &a, from_string("1").unwrap());
+        assert_eq!(bignum_to_decimal(&a, *parallel), nines);
+        assert_eq!(bignum_to_decimal(&b, *parallel), power_of_ten);
+        assert_eq!(bignum_to_decimal(&-a, *parallel), format!("-{}", nines));
+    }
+
+    let mut padded = "-".to_string();
+    padded.push_str(&"0".repeat(3000));
+    padded.push_str("42");
+    assert_eq!(from_string(&padded).unwrap().to_string(), "-42");
+}
+
+#[test]
+fn primitive_conversion_test() {
+    assert_eq!(Bignum::from(0u8).to_string(), "0");
+    assert_eq!(Bignum::from(u64::MAX).to_string(), "18446744073709551615");
+    assert_eq!(Bignum::from(i64::MIN).to_string(), "-9223372036854775808");
+    assert_eq!(Bignum::from(u128::MAX).to_string(), "340282366920938463463374607431768211455");
+    assert_eq!(Bignum::from(i128::MIN).to_string(), "-170141183460469231731687303715884105728");
+    assert_eq!(Bignum::from(-1isize).to_string(), "-1");
+
+    let a = from_string("-128").unwrap();
+    assert_eq!(i8::try_from(&a), Ok(-128));
+    assert_eq!(i16::try_from(&a), Ok(-128));
+    assert_eq!(u8::try_from(&a), Err(TryFromBignumError));
+    assert_eq!(i8::try_from(&-&a), Err(TryFromBignumError));
+    assert_eq!(u8::try_from(&-&a), Ok(128));
+
+    let min = Bignum::from(i128::MIN);
+    assert_eq!(i128::try_from(&min), Ok(i128::MIN));
+    assert_eq!(i128::try_from(&(&min - Bignum::from(1u8))), Err(TryFromBignumError));
+    assert_eq!(u128::try_from(&(Bignum::from(u128::MAX) + Bignum::from(1u8))), Err(TryFromBignumError));
+    assert_eq!(u32::try_from(&from_string("-0").unwrap()), Ok(0));
+    assert_eq!(from_string("-0").unwrap().to_i8_wrapping(), 0);
+
+    let big = from_string("-340282366920938463463374607431768211457").unwrap(); // -(2^128 + 1)
+    assert_eq!(big.to_u128_wrapping(), u128::MAX);
+    assert_eq!(big.to_i64_wrapping(), -1);
+    assert_eq!(from_string("300").unwrap().to_u8_wrapping(), 44);
+    assert_eq!(from_string("-300").unwrap().to_u8_wrapping(), 212);
+    assert_eq!(from_string("200").unwrap().to_i8_wrapping(), -56);
+    assert_eq!(TryFromBignumError.to_string(), "bignum out of range for the target integer type");
+}
+
+#[test]
+fn float_conversion_test() {
+    // Every integer on either side of the points where f64 and f32 start rounding
+    for base in [1u64 << 24, 1 << 53, 1 << 54, u64::MAX - 5000].iter() {
+        for x in base - 3000..base + 3000 {
+            assert_eq!(Bignum::from(x).to_f64(), x as f64);
+            assert_eq!(Bignum::from(x).to_f32(), x as f32);
+            assert_eq!(Bignum::from(-(x as i128)).to_f64(), -(x as f64));
+        }
+    }
+    for shift in 0..128 {
+        for x in [1u128 << shift, (1 << shift) - 1, (1 << shift) + (1 << shift) / 3].iter() {
+            assert_eq!(Bignum::from(*x).to_f64(), *x as f64);
+            assert_eq!(Bignum::from(*x).to_f32(), *x as f32);
+        }
+    }
+
+    let two = Bignum::from(2u8);
+    let power = |n: usize| (0..n).fold(Bignum::from(1u8), |acc, _| &acc * &two);
+
+    // f64::MAX is 2^1024 - 2^971, halfway to the next value up is 2^1024 - 2^970
+    let max = power(1024) - power(971);
+    assert_eq!(max.to_f64(), f64::MAX);
+    assert_eq!((&max + power(970) - Bignum::from(1u8)).to_f64(), f64::MAX);
+    assert_eq!((&max + power(970)).to_f64(), f64::INFINITY);
+    assert_eq!((-power(5000)).to_f64(), f64::NEG_INFINITY);
+    assert_eq!(power(200).to_f32(), f32::INFINITY);
+    assert_eq!((power(128) - power(103) - Bignum::from(1u8)).to_f32(), f32::MAX);
+    assert_eq!((power(128) - power(103)).to_f32(), f32::INFINITY);
+
+    // A tie far below the top bits is broken by the sticky bit
+    let tie = power(300) + power(247);
+    assert_eq!(tie.to_f64(), 2f64.powi(300));
+    assert_eq!((&tie + Bignum::from(1u8)).to_f64(), 2f64.powi(300) + 2f64.powi(248));
+    assert_eq!((&tie + power(248)).to_f64(), 2f64.powi(300) + 2f64.powi(249));
+    assert_eq!(from_string("-0").unwrap().to_f64(), 0.0);
+
+    assert_eq!(Bignum::from_f64(f64::MAX).unwrap(), max);
+    assert_eq!(Bignum::from_f64(-2f64.powi(1000)).unwrap(), -power(1000));
+    assert_eq!(Bignum::from_f64(f32::MAX as f64).unwrap(), power(128) - power(104));
+    assert_eq!(Bignum::from_f32(-1.5e10).unwrap().to_string(), "-15000000512");
+    assert_eq!(Bignum::from_f64(123.99).unwrap().to_string(), "123");
+    assert_eq!(Bignum::from_f64(-123.99).unwrap().to_string(), "-123");
+    assert_eq!(Bignum::from_f64(-0.5).unwrap(), Bignum::from(0u8));
+    assert_eq!(Bignum::from_f64(-0.0).unwrap(), Bignum::from(0u8));
+    assert_eq!(Bignum::from_f64(5e-324).unwrap(), Bignum::from(0u8));
+    assert_eq!(Bignum::from_f64(-f64::MIN_POSITIVE).unwrap(), Bignum::from(0u8));
+    assert_eq!(Bignum::from_f64(f64::NAN), Err(FromFloatError::NaN));
+    assert_eq!(Bignum::from_f64(f64::NEG_INFINITY), Err(FromFloatError::Infinite));
+    assert_eq!(Bignum::from_f32(f32::INFINITY), Err(FromFloatError::Infinite));
+}
+
+#[test]
+fn canonical_zero_test() {
+    let zero = from_string("0").unwrap();
+    let negative_zero = from_string("-000").unwrap();
+    assert_eq!(negative_zero.limbs(), &[]);
+    assert_eq!(negative_zero.sign(), Sign::Nonnegative);
+
+    let a = from_string("-123456789012345678901234567890").unwrap();
+    for result in [&a - &a, &a + (-&a), &a * &zero, bignum_long_mult(&a, &zero),
+                   bignum_karatsuba_mult(&a, &zero, 2, false), -&zero,
+                   bignum_divrem(&a, &a).unwrap().1, bignum_divrem(&zero, &a).unwrap().0].iter() {
+        assert_eq!(result.limbs(), &[]);
+        assert_eq!(result.sign(), Sign::Nonnegative);
+    }
+
+    let padded_zero = from_string("-0000000000000000000000").unwrap();
+    let padded_seven = from_string("+0000000000000000000007").unwrap();
+    assert_eq!(padded_zero, zero);
+    assert_eq!(padded_seven, Bignum::from(7u8));
+
+    let mut hashed = HashMap::new();
+    hashed.insert(zero.clone(), "zero");
+    hashed.insert(Bignum::from(7u8), "seven");
+    assert_eq!(hashed.get(&negative_zero), Some(&"zero"));
+    assert_eq!(hashed.get(&padded_zero), Some(&"zero"));
+    assert_eq!(hashed.get(&padded_seven), Some(&"seven"));
+    assert_eq!(hashed.get(&Bignum::from(-7i8)), None);
+
+    let mut ordered = BTreeMap::new();
+    ordered.insert(padded_zero, 1);
+    ordered.insert(negative_zero, 2);
+    ordered.insert(padded_seven, 3);
+    ordered.insert(Bignum::from(-7i8), 4);
+    assert_eq!(ordered.len(), 3);
+    assert_eq!(ordered.get(&zero), Some(&2));
+    assert_eq!(ordered.keys().map(|k| k.to_string()).collect::<Vec<_>>(), ["-7", "0", "7"]);
+}
+
+#[test]
+fn biguint_test() {
+    let a = BigUint::from(u64::MAX);
+    let b = BigUint::from(1u8);
+    assert_eq!(biguint_add(&a, &b).limbs(), &[0, 0, 1]);
+    assert_eq!(biguint_sub(&biguint_add(&a, &b), &b), Ok(a.clone()));
+    assert_eq!(biguint_sub(&b, &a), Err(UnderflowError));
+    assert_eq!(biguint_long_mult(&a, &a).to_string(), "340282366920938463426481119284349108225");
+    assert_eq!(&a * &a, biguint_long_mult(&a, &a));
+    assert_eq!((&a + &b) - &a, b);
+    assert!(BigUint::from(0u8).is_zero());
+    assert_eq!(format!("{:?}", b), "BigUint(1)");
+
+    assert_eq!(BigUint::from_parts(vec![1, 0]), Err(InvalidLimbError::LeadingZero { index: 1 }));
+    assert_eq!(BigUint::from_parts(vec![]), Ok(BigUint::from(0u8)));
+
+    // Conversions keep every limb, and only fail on a negative Bignum
+    let big = from_string("123456789012345678901234567890123456789012345678901234567890").unwrap();
+    let magnitude = BigUint::try_from(&big).unwrap();
+    assert_eq!(magnitude.limbs(), big.limbs());
+    assert_eq!(Bignum::from(magnitude.clone()), big);
+    assert_eq!(BigUint::try_from(-&big), Err(TryFromBignumError));
+    assert_eq!(big.magnitude(), &magnitude);
+    assert_eq!(Bignum::from_sign_magnitude(Sign::Negative, magnitude.clone()), -&big);
+    assert_eq!(Bignum::from_sign_magnitude(Sign::Negative, BigUint::from(0u8)).sign(), Sign::Nonnegative);
+    assert_eq!((-&big).into_sign_magnitude(), (Sign::Negative, magnitude));
+}
+
+#[test]
+#[should_panic(expected = "attempt to subtract with overflow")]
+fn biguint_underflow_test() {
+    let _ = BigUint::from(1u8) - BigUint::from(2u8);
+}
+
+#[test]
+fn view_test() {
+    let a = from_string("-123456789012345678901234567890123456789012345678901234567890").unwrap();
+    let b = from_string("987654321098765432109876543210").unwrap();
+
+    // Views and references are interchangeable
+    assert_eq!(bignum_add(a.view(), &b), &a + &b);
+    assert_eq!(bignum_sub(&a, b.view()), &a - &b);
+    assert_eq!(bignum_sub(b.view(), b.view()), from_string("0").unwrap());
+    assert_eq!(bignum_long_mult(a.view(), b.view()), &a * &b);
+    assert_eq!(bignum_karatsuba_mult(a.view(), &b, 2, true), &a * &b);
+    assert_eq!(a.view().to_bignum(), a);
+    assert_eq!(BignumRef::from(&a).sign(), Sign::Negative);
+
+    // A view of the top limbs of a is a divided by a power of the base, rounded towards zero
+    let limbs = a.limbs();
+    let top = BignumRef::from_parts(Sign::Negative, &limbs[2..]).unwrap();
+    let base_squared = from_string("18446744073709551616").unwrap();
+    assert_eq!(top.limbs(), &limbs[2..]);
+    assert_eq!(top.to_bignum(), bignum_divrem(&a, &base_squared).unwrap().0);
+    assert_eq!(bignum_karatsuba_mult(top, top, 2, false), bignum_long_mult(&top.to_bignum(), &top.to_bignum()));
+
+    assert_eq!(BignumRef::from_parts(Sign::Nonnegative, &[1, 0]).err(), Some(InvalidLimbError::LeadingZero { index: 1 }));
+    assert_eq!(BignumRef::from_parts(Sign::Negative, &[]).err(), Some(InvalidLimbError::NegativeZero));
+}
+
+#[test]
+fn limbs_test() {
+    const MAX: Limb = Limb::MAX;
+
+    // Carries and borrows ripple all the way through and fall off the top
+    let mut a = [MAX, MAX, MAX];
+    assert!(limbs::add_1(&mut a, 1));
+    assert_eq!(a, [0, 0, 0]);
+    assert!(limbs::sub_1(&mut a, 1));
+    assert_eq!(a, [MAX, MAX, MAX]);
+    assert!(limbs::add_n(&mut a, &[1, 0, 0]));
+    assert_eq!(a, [0, 0, 0]);
+    assert!(limbs::sub_n(&mut a, &[0, 1, 0]));
+    assert_eq!(a, [0, MAX, MAX]);
+    assert!(limbs::add(&mut a, &[0, 1]));
+    assert_eq!(a, [0, 0, 0]);
+    assert!(!limbs::add(&mut a, &[5]));
+    assert_eq!(a, [5, 0, 0]);
+    assert!(!limbs::add_n(&mut [], &[]));
+
+    // (BASE - 1)^2 = (BASE - 2) * BASE + 1
+    let mut a = [MAX, MAX];
+    assert_eq!(limbs::mul_1(&mut a, MAX), MAX - 1);
+    assert_eq!(a, [1, MAX]);
+    let mut r = [MAX, MAX];
+    assert_eq!(limbs::addmul_1(&mut r, &[MAX, MAX], MAX), MAX);
+    assert_eq!(r, [0, MAX]);
+    let mut r = [0, 0];
+    assert_eq!(limbs::submul_1(&mut r, &[MAX, MAX], MAX), MAX);
+    assert_eq!(r, [MAX, 0]);
+
+    let mut a = [0x8000_0001, 0x8000_0000];
+    assert_eq!(limbs::lshift(&mut a, 1), 1);
+    assert_eq!(a, [2, 1]);
+    assert_eq!(limbs::rshift(&mut a, 2), 0x8000_0000);
+    assert_eq!(a, [0x4000_0000, 0]);
+    assert_eq!(limbs::lshift(&mut a, 0), 0);
+
+    let mut a = [1, 1];
+    assert_eq!(limbs::divrem_1(&mut a, 3), 2);
+    assert_eq!(a, [0x5555_5555, 0]);
+    let mut a = [MAX - 2, MAX, 2];
+    limbs::divexact_1(&mut a, 3);
+    assert_eq!(a, [MAX, MAX, 0]);
+    let mut a = [0, 0, 12];
+    limbs::divexact_1(&mut a, 24);
+    assert_eq!(a, [0, 0x8000_0000, 0]);
+
+    assert_eq!(limbs::cmp(&[1, 0, 0], &[1]), Ordering::Equal);
+    assert_eq!(limbs::cmp(&[0, 1], &[MAX]), Ordering::Greater);
+    assert_eq!(limbs::cmp(&[], &[0, 0]), Ordering::Equal);
+    assert_eq!(limbs::significant_len(&[1, 0, 2, 0, 0]), 3);
+}
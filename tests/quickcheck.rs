@@ -3,23 +3,26 @@ extern crate bignum;
 extern crate quickcheck;
 
 use bignum::types::*;
+use bignum::biguint::*;
 use bignum::basic_ops::*;
 use bignum::karatsuba::*;
+use bignum::toom_cook::*;
+use bignum::ntt::*;
+use bignum::mul::*;
+use bignum::burnikel_ziegler::*;
+use bignum::newton::*;
+use bignum::decimal::*;
+use bignum::limbs;
 use std::cmp::Ordering;
+use std::convert::TryFrom;
 
 /**
  * Tests which failed quickcheck at some point, to preserve the witnesses
  */
 #[test]
 fn regression_test_1() {
-    let b1 = Bignum {
-        sign: Sign::Nonnegative,
-        parts: vec!(0, 0, 0, 0, 1),
-    };
-    let b2 = Bignum {
-        sign: Sign::Negative,
-        parts: vec!(0, 0, 0, 0, 0, 0, 0, 0, 1)
-    };
+    let b1 = Bignum::from_parts(Sign::Nonnegative, vec!(0, 0, 0, 0, 1)).unwrap();
+    let b2 = Bignum::from_parts(Sign::Negative, vec!(0, 0, 0, 0, 0, 0, 0, 0, 1)).unwrap();
 
     let long_mult = bignum_long_mult(&b1, &b2);
     let karatsuba_mult = bignum_karatsuba_mult(&b1, &b2, 4, true);
@@ -30,7 +33,6 @@ fn bool_to_sign(b: bool) -> Sign {
     if b { Sign::Nonnegative } else { Sign::Negative }
 }
 
-#[test]
 quickcheck! {
     fn long_mult_same_as_karatsuba(
         parts1: Vec<u32>,
@@ -38,18 +40,424 @@ quickcheck! {
         sign1: bool,
         sign2: bool
     ) -> bool {
-        let b1 = Bignum {
-            sign: bool_to_sign(sign1),
-            parts: parts1,
-        };
+        let b1 = valid_bignum(parts1, sign1);
+        let b2 = valid_bignum(parts2, sign2);
 
-        let b2 = Bignum {
-            sign: bool_to_sign(sign2),
-            parts: parts2,
-        };
-        
         let long_mult = bignum_long_mult(&b1, &b2);
         let karatsuba_mult = bignum_karatsuba_mult(&b1, &b2, 4, true);
         long_mult.cmp(&karatsuba_mult) == Ordering::Equal
     }
 }
+
+quickcheck! {
+    fn long_mult_same_as_toom3(
+        parts1: Vec<u32>,
+        parts2: Vec<u32>,
+        sign1: bool,
+        sign2: bool
+    ) -> bool {
+        let b1 = valid_bignum(parts1, sign1);
+        let b2 = valid_bignum(parts2, sign2);
+
+        let long_mult = bignum_long_mult(&b1, &b2);
+        let toom3_mult = bignum_toom3_mult(&b1, &b2, 4, 2, true);
+        long_mult.cmp(&toom3_mult) == Ordering::Equal
+            && bignum_toom3_mult(&b1, &b1, 4, 2, false) == bignum_long_mult(&b1, &b1.clone())
+    }
+}
+
+quickcheck! {
+    fn long_mult_same_as_toom4(
+        parts1: Vec<u32>,
+        parts2: Vec<u32>,
+        sign1: bool,
+        sign2: bool
+    ) -> bool {
+        let b1 = valid_bignum(parts1, sign1);
+        let b2 = valid_bignum(parts2, sign2);
+
+        let long_mult = bignum_long_mult(&b1, &b2);
+        let toom4_mult = bignum_toom4_mult(&b1, &b2, 4, 2, true);
+        long_mult.cmp(&toom4_mult) == Ordering::Equal
+            && bignum_toom4_mult(&b1, &b1, 4, 2, false) == bignum_long_mult(&b1, &b1.clone())
+            && bignum_toom_mult(&b1, &b2, 8, 4, 2, true) == long_mult
+    }
+}
+
+quickcheck! {
+    fn long_mult_same_as_ntt(
+        parts1: Vec<u32>,
+        parts2: Vec<u32>,
+        sign1: bool,
+        sign2: bool
+    ) -> bool {
+        let b1 = valid_bignum(parts1, sign1);
+        let b2 = valid_bignum(parts2, sign2);
+
+        let long_mult = bignum_long_mult(&b1, &b2);
+        let ntt_mult = bignum_ntt_mult(&b1, &b2, true);
+        long_mult.cmp(&ntt_mult) == Ordering::Equal
+            && bignum_ntt_mult(&b1, &b1, false) == bignum_long_mult(&b1, &b1.clone())
+    }
+}
+
+fn valid_bignum(mut parts: Vec<u32>, sign: bool) -> Bignum {
+    while parts.last() == Some(&0) {
+        parts.pop();
+    }
+    let sign = if parts.is_empty() { Sign::Nonnegative } else { bool_to_sign(sign) };
+    Bignum::from_parts(sign, parts).unwrap()
+}
+
+fn abs(b: &Bignum) -> Bignum {
+    Bignum::from_parts(Sign::Nonnegative, b.limbs().to_vec()).unwrap()
+}
+
+quickcheck! {
+    fn divrem_reconstructs_dividend(
+        parts1: Vec<u32>,
+        parts2: Vec<u32>,
+        sign1: bool,
+        sign2: bool
+    ) -> bool {
+        let a = valid_bignum(parts1, sign1);
+        let b = valid_bignum(parts2, sign2);
+
+        match bignum_divrem(&a, &b) {
+            Err(DivideByZeroError) => b.limbs().is_empty(),
+            Ok((q, r)) => {
+                let reconstructed = bignum_add(&bignum_long_mult(&q, &b), &r);
+                reconstructed.cmp(&a) == Ordering::Equal
+                    && abs(&r) < abs(&b)
+                    && (r.limbs().is_empty() || r.sign() == a.sign())
+            }
+        }
+    }
+}
+
+quickcheck! {
+    fn burnikel_ziegler_same_as_divrem(
+        parts1: Vec<u32>,
+        parts2: Vec<u32>,
+        sign1: bool,
+        sign2: bool
+    ) -> bool {
+        let a = valid_bignum(parts1, sign1);
+        let b = valid_bignum(parts2, sign2);
+
+        match (bignum_divrem(&a, &b), bignum_burnikel_ziegler_divrem(&a, &b, 2, true)) {
+            (Ok(expected), Ok(actual)) => expected == actual,
+            (Err(_), Err(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+quickcheck! {
+    fn newton_reciprocal_same_as_divrem(parts: Vec<u32>, sign: bool, extra_limbs: u8) -> bool {
+        let b = valid_bignum(parts, sign);
+        let precision = b.limbs().len() + extra_limbs as usize;
+        let mut limbs = vec![0; precision];
+        limbs.push(1);
+        let power = Bignum::from_parts(Sign::Nonnegative, limbs).unwrap();
+
+        match (bignum_divrem(&power, &b), bignum_reciprocal(&b, precision)) {
+            (Ok((expected, _)), Ok(actual)) => expected == actual,
+            (Err(_), Err(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+quickcheck! {
+    fn newton_divrem_same_as_divrem(
+        parts1: Vec<u32>,
+        parts2: Vec<u32>,
+        sign1: bool,
+        sign2: bool
+    ) -> bool {
+        let a = valid_bignum(parts1, sign1);
+        let b = valid_bignum(parts2.into_iter().take(8).collect(), sign2);
+
+        match (bignum_divrem(&a, &b), bignum_newton_divrem(&a, &b)) {
+            (Ok(expected), Ok(actual)) => expected == actual,
+            (Err(_), Err(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+quickcheck! {
+    fn assign_ops_same_as_basic_ops(
+        parts1: Vec<u32>,
+        parts2: Vec<u32>,
+        sign1: bool,
+        sign2: bool
+    ) -> bool {
+        let a = valid_bignum(parts1, sign1);
+        let b = valid_bignum(parts2, sign2);
+
+        let mut sum = a.clone();
+        sum += &b;
+        let mut difference = a.clone();
+        difference -= &b;
+
+        sum.cmp(&bignum_add(&a, &b)) == Ordering::Equal
+            && difference.cmp(&bignum_sub(&a, &b)) == Ordering::Equal
+    }
+}
+
+quickcheck! {
+    fn radix_round_trip(parts: Vec<u32>, sign: bool, radix: u8) -> bool {
+        let b = valid_bignum(parts, sign);
+        let radix = 2 + radix as u32 % 35;
+
+        Bignum::from_str_radix(&b.to_str_radix(radix), radix).unwrap().cmp(&b) == Ordering::Equal
+    }
+}
+
+quickcheck! {
+    fn decimal_round_trip(parts: Vec<u32>, sign: bool, parallel: bool) -> bool {
+        let b = valid_bignum(parts, sign);
+        let s = bignum_to_decimal(&b, parallel);
+
+        s == b.to_string() && bignum_from_decimal(&s, !parallel).unwrap().cmp(&b) == Ordering::Equal
+    }
+}
+
+quickcheck! {
+    fn primitive_round_trip(x: i64, y: u64, high: i64, low: u64) -> bool {
+        let z = ((high as i128) << 64) | low as i128;
+        let (bx, by, bz) = (Bignum::from(x), Bignum::from(y), Bignum::from(z));
+
+        bx.to_string() == x.to_string() && by.to_string() == y.to_string() && bz.to_string() == z.to_string()
+            && i64::try_from(&bx) == Ok(x) && u64::try_from(&by) == Ok(y) && i128::try_from(&bz) == Ok(z)
+            && u64::try_from(&bx).ok() == u64::try_from(x).ok()
+            && i32::try_from(&bz).ok() == i32::try_from(z).ok()
+            && bz.to_u32_wrapping() == z as u32 && bx.to_u64_wrapping() == x as u64
+    }
+}
+
+quickcheck! {
+    fn wrapping_same_as_mod(parts: Vec<u32>, sign: bool) -> bool {
+        let b = valid_bignum(parts, sign);
+        let modulus = Bignum::from(1u128 << 64) * Bignum::from(1u128 << 64);
+        let (_, r) = bignum_divrem_euclid(&b, &modulus).unwrap();
+
+        u128::try_from(&r) == Ok(b.to_u128_wrapping())
+    }
+}
+
+quickcheck! {
+    fn to_float_same_as_cast(x: i64, y: u64, high: u64, low: u64) -> bool {
+        let z = ((high as u128) << 64) | low as u128;
+
+        Bignum::from(x).to_f64() == x as f64 && Bignum::from(x).to_f32() == x as f32
+            && Bignum::from(y).to_f64() == y as f64 && Bignum::from(y).to_f32() == y as f32
+            && Bignum::from(z).to_f64() == z as f64 && Bignum::from(z).to_f32() == z as f32
+    }
+}
+
+quickcheck! {
+    fn from_float_same_as_cast(x: i64, y: u64, scale: i8) -> bool {
+        let fx = x as f64 * 2f64.powi(scale as i32 % 16);
+        let fy = y as f64 / 3.0;
+
+        Bignum::from_f64(fx).unwrap() == Bignum::from(fx as i128)
+            && Bignum::from_f64(fy).unwrap() == Bignum::from(fy as u64)
+            && Bignum::from_f32(fy as f32).unwrap() == Bignum::from(fy as f32 as u64)
+    }
+}
+
+quickcheck! {
+    fn float_round_trip(parts: Vec<u32>, sign: bool) -> bool {
+        let b = valid_bignum(parts, sign);
+        let f = b.to_f64();
+
+        // Integral floats survive a round trip, and rounding moves by at most half an ulp
+        f.is_infinite() || {
+            let back = Bignum::from_f64(f).unwrap();
+            let error = abs(&(&back - &b));
+            back.to_f64() == f && Bignum::from_f64(f.abs() / 2f64.powi(53)).unwrap() >= error
+        }
+    }
+}
+
+fn is_canonical(b: &Bignum) -> bool {
+    Bignum::from_parts(b.sign(), b.limbs().to_vec()).is_ok()
+}
+
+quickcheck! {
+    fn results_are_canonical(parts1: Vec<u32>, parts2: Vec<u32>, sign1: bool, sign2: bool) -> bool {
+        let a = valid_bignum(parts1, sign1);
+        let b = valid_bignum(parts2, sign2);
+        let mut results = vec![&a + &b, &a - &b, &a - &a, -&a, bignum_long_mult(&a, &b),
+                               bignum_karatsuba_mult(&a, &b, 2, false), from_string(&a.to_string()).unwrap()];
+        if let Ok((q, r)) = bignum_divrem(&a, &b) {
+            results.push(q);
+            results.push(r);
+        }
+
+        results.iter().all(is_canonical)
+    }
+}
+
+quickcheck! {
+    fn biguint_same_as_bignum(parts1: Vec<u32>, parts2: Vec<u32>) -> bool {
+        let a = valid_bignum(parts1, true);
+        let b = valid_bignum(parts2, true);
+        let (x, y) = (a.magnitude(), b.magnitude());
+
+        let difference = match biguint_sub(x, y) {
+            Ok(d) => Bignum::from(d) == &a - &b,
+            Err(UnderflowError) => a < b,
+        };
+        difference
+            && Bignum::from(biguint_add(x, y)) == &a + &b
+            && Bignum::from(biguint_long_mult(x, y)) == bignum_long_mult(&a, &b)
+            && biguint_karatsuba_mult(x, y, 2, true) == biguint_long_mult(x, y)
+            && x.cmp(y) == a.cmp(&b)
+    }
+}
+
+quickcheck! {
+    fn views_same_as_owned(parts1: Vec<u32>, parts2: Vec<u32>, split: usize, cutoff: usize) -> bool {
+        let a = valid_bignum(parts1, true);
+        let b = valid_bignum(parts2, false);
+
+        // Any top slice of a's limbs is itself a valid view
+        let split = if a.limbs().is_empty() { 0 } else { split % a.limbs().len() };
+        let top = BignumRef::from_parts(Sign::Nonnegative, &a.limbs()[split..]).unwrap();
+        let top_owned = top.to_bignum();
+        let cutoff = 2 + cutoff % 8;
+
+        bignum_karatsuba_mult(top, &b, cutoff, false) == bignum_long_mult(&top_owned, &b)
+            && bignum_karatsuba_mult(&b, top, cutoff, true) == bignum_long_mult(&top_owned, &b)
+            && bignum_add(top, b.view()) == &top_owned + &b
+            && bignum_sub(top, b.view()) == &top_owned - &b
+    }
+}
+
+// The value of a slice of limbs, leading zeroes and all
+fn limbs_value(parts: &[u32]) -> Bignum {
+    valid_bignum(parts.to_vec(), true)
+}
+
+quickcheck! {
+    fn limbs_same_as_bignum(parts1: Vec<u32>, parts2: Vec<u32>, x: u32, bits: u8) -> bool {
+        let n = parts1.len().min(parts2.len());
+        let (a, b) = (&parts1[..n], &parts2[..n]);
+        let base_n = limbs_value(&[vec![0; n], vec![1]].concat());
+        let (va, vb, vx) = (limbs_value(a), limbs_value(b), Bignum::from(x));
+        let bits = bits as u32 % 32;
+
+        // Each kernel leaves the low n limbs in place and returns what fell off the top
+        let mut sum = a.to_vec();
+        let carry = limbs::add_n(&mut sum, b);
+        let mut difference = a.to_vec();
+        let borrow = limbs::sub_n(&mut difference, b);
+        let mut product = a.to_vec();
+        let product_top = limbs::mul_1(&mut product, x);
+        let mut addmul = b.to_vec();
+        let addmul_top = limbs::addmul_1(&mut addmul, a, x);
+        let mut submul = b.to_vec();
+        let submul_top = limbs::submul_1(&mut submul, a, x);
+        let mut shifted = a.to_vec();
+        let shifted_top = limbs::lshift(&mut shifted, bits);
+        let mut unshifted = a.to_vec();
+        let unshifted_low = limbs::rshift(&mut unshifted, bits);
+        let low_bits = if n == 0 || bits == 0 { 0 } else { a[0] << (32 - bits) };
+
+        let top = |low: &[u32], high: u32| &limbs_value(low) + &(&base_n * &Bignum::from(high));
+        top(&sum, carry as u32) == &va + &vb
+            && &top(&difference, 0) - &(&base_n * &Bignum::from(borrow as u32)) == &va - &vb
+            && top(&product, product_top) == &va * &vx
+            && top(&addmul, addmul_top) == &vb + &(&va * &vx)
+            && &limbs_value(&submul) - &(&base_n * &Bignum::from(submul_top)) == &vb - &(&va * &vx)
+            && top(&shifted, shifted_top) == &va * &Bignum::from(1u64 << bits)
+            && limbs_value(&unshifted) == &va / &Bignum::from(1u64 << bits)
+            && unshifted_low == low_bits
+            && limbs::cmp(&parts1, &parts2) == limbs_value(&parts1).cmp(&limbs_value(&parts2))
+    }
+}
+
+quickcheck! {
+    fn karatsuba_into_same_as_long_mult(parts1: Vec<u32>, parts2: Vec<u32>, cutoff: usize, junk: u32) -> bool {
+        let a = valid_bignum(parts1, true);
+        let b = valid_bignum(parts2, true);
+        let (x, y) = (a.limbs(), b.limbs());
+        let mut out = vec![junk; x.len() + y.len()];
+        let mut scratch = vec![junk; karatsuba_scratch_len(x.len().max(y.len()))];
+        karatsuba_mult_into(x, y, &mut out, &mut scratch, 2 + cutoff % 8);
+
+        limbs_value(&out) == bignum_long_mult(&a, &b)
+    }
+}
+
+quickcheck! {
+    fn lopsided_mult_same_as_long_mult(parts1: Vec<u32>, parts2: Vec<u32>, copies: u8, cutoff: usize) -> bool {
+        // The long side is several copies of the short one plus some more
+        let short = valid_bignum(parts1, true);
+        let mut long_parts = short.limbs().repeat(2 + copies as usize % 6);
+        long_parts.extend(parts2);
+        let long = valid_bignum(long_parts, false);
+        let cutoff = 2 + cutoff % 8;
+
+        let expected = bignum_long_mult(&long, &short);
+        bignum_karatsuba_mult(&long, &short, cutoff, false) == expected
+            && bignum_karatsuba_mult(&short, &long, cutoff, true) == expected
+            && bignum_toom3_mult(&long, &short, 2 + cutoff, cutoff, false) == expected
+            && bignum_toom4_mult(&short, &long, 2 + cutoff, cutoff, false) == expected
+    }
+}
+
+quickcheck! {
+    fn sqr_same_as_long_mult(parts: Vec<u32>, sign: bool, cutoff: usize) -> bool {
+        let a = valid_bignum(parts, sign);
+        let copy = a.clone();
+        let cutoff = 2 + cutoff % 8;
+
+        // A copy is a different object, so it takes the general multiplication
+        let expected = bignum_long_mult(&a, &copy);
+        bignum_sqr(&a) == expected
+            && bignum_long_mult(&a, &a) == expected
+            && bignum_karatsuba_sqr(&a, cutoff, false) == expected
+            && bignum_karatsuba_mult(&a, &a, cutoff, true) == expected
+            && bignum_karatsuba_mult(&a, &copy, cutoff, false) == expected
+    }
+}
+
+quickcheck! {
+    fn divexact_same_as_divrem(parts: Vec<u32>, divisor: u32) -> bool {
+        let divisor = divisor.max(1);
+        let a = valid_bignum(parts, true);
+        let mut multiple = a.limbs().to_vec();
+        let top = limbs::mul_1(&mut multiple, divisor);
+        multiple.push(top);
+        limbs::divexact_1(&mut multiple, divisor);
+
+        limbs_value(&multiple) == a
+    }
+}
+
+quickcheck! {
+    fn mul_same_as_long_mult(parts1: Vec<u32>, parts2: Vec<u32>, sign1: bool, sign2: bool, thresholds: (u8, u8, u8, u8)) -> bool {
+        let a = valid_bignum(parts1, sign1);
+        let b = valid_bignum(parts2, sign2);
+        let (karatsuba, toom3, toom4, ntt) = thresholds;
+        let karatsuba_threshold = 2 + karatsuba as usize % 16;
+        let toom3_threshold = karatsuba_threshold + 2 + toom3 as usize % 32;
+        let toom4_threshold = toom3_threshold + 1 + toom4 as usize % 64;
+        let config = MulConfig {
+            karatsuba_threshold,
+            toom3_threshold,
+            toom4_threshold,
+            ntt_threshold: toom4_threshold + 1 + ntt as usize,
+            parallel: ntt % 2 == 0,
+            parallel_depth: karatsuba as usize % 4,
+            parallel_grain: toom3 as usize % 16,
+        };
+
+        bignum_mul_with(&a, &b, &config) == bignum_long_mult(&a, &b) && bignum_mul(&a, &b) == bignum_long_mult(&a, &b)
+    }
+}